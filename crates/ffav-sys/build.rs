@@ -9,8 +9,8 @@ fn main() {
         .header("wrapper.h")
         .parse_callbacks(Box::new(bindgen::CargoCallbacks))
         .allowlist_type("^AV.*")
-        .allowlist_function("^(av_|avformat_|avcodec_|avfilter_).*")
-        .allowlist_var("^AV(ERROR|FILTER|SEEK)?_.*")
+        .allowlist_function("^(av_|avformat_|avcodec_|avfilter_|avio_|swr_|sws_).*")
+        .allowlist_var("^(AV(ERROR|FILTER|SEEK|FMT|IO)?|SWS)_.*")
         // .allowlist_var("^AVSEEK_.*")
         // .allowlist_var("^AVERROR_.*")
         // .allowlist_var("^AVFILTER_FLAG.*")
@@ -55,6 +55,12 @@ fn main() {
         .probe("libswresample")
         .unwrap();
 
+    pkg_config::Config::new()
+        .atleast_version("5")
+        //.statik(true)
+        .probe("libswscale")
+        .unwrap();
+
     pkg_config::Config::new()
         .atleast_version("56")
         //.statik(true)
@@ -39,3 +39,5 @@ pub const AVERROR_PATCHWELCOME: i32 = ffaverr('P', 'A', 'W', 'E');
 pub const AVERROR_PROTOCOL_NOT_FOUND: i32 = ffaverr(0xF8 as char, 'P', 'R', 'O');
 pub const AVERROR_BSF_NOT_FOUND: i32 = ffaverr(0xF8 as char, 'B', 'S', 'F');
 pub const AVERROR_STREAM_NOT_FOUND: i32 = ffaverr(0xF8 as char, 'S', 'T', 'R');
+pub const AVERROR_INPUT_CHANGED: i32 = -0x636e6701;
+pub const AVERROR_OUTPUT_CHANGED: i32 = -0x636e6702;
@@ -6,3 +6,7 @@
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 
 pub mod err;
+
+// This is defined through a cast in the C headers which bindgen can't evaluate
+/// Undefined timestamp value, used wherever a timestamp is unknown
+pub const AV_NOPTS_VALUE: i64 = i64::MIN;
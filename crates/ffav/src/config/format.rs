@@ -3,7 +3,7 @@
 use ffav_sys::AVFormatContext;
 
 use crate::util::{
-    marker::Input,
+    marker::{Input, Output},
     time::{TimeBase, TimeBaseTicks, Timestamp},
 };
 use std::{marker::PhantomData, num::NonZeroI64};
@@ -18,8 +18,8 @@ pub struct FormatConfig<IO> {
     // The following members are only set during demuxing, and thus are only
     // available for `FormatConfig<Input>` types.
     //
-    start_time: TimeBaseTicks,
-    duration: TimeBaseTicks,
+    start_time: Option<TimeBaseTicks>,
+    duration: Option<TimeBaseTicks>,
     bit_rate: Option<NonZeroI64>,
 
     //
@@ -35,8 +35,8 @@ pub struct FormatConfig<IO> {
 impl FormatConfig<Input> {
     pub(crate) unsafe fn from_input_fmt_ctx(ctx: *mut AVFormatContext) -> Self {
         FormatConfig {
-            start_time: TimeBaseTicks::new((*ctx).start_time as u64),
-            duration: TimeBaseTicks::new((*ctx).duration as u64),
+            start_time: TimeBaseTicks::from_av_timestamp((*ctx).start_time),
+            duration: TimeBaseTicks::from_av_timestamp((*ctx).duration),
             num_streams: (*ctx).nb_streams as usize,
 
             bit_rate: NonZeroI64::new((*ctx).bit_rate),
@@ -47,13 +47,19 @@ impl FormatConfig<Input> {
     }
 
     /// The time of the first frame of the Format
-    pub fn start_time(&self) -> Timestamp {
-        self.start_time.to_timestamp(TimeBase::DEFAULT)
+    ///
+    /// Will return None if the value is not known
+    pub fn start_time(&self) -> Option<Timestamp> {
+        self.start_time
+            .map(|ticks| ticks.to_timestamp(TimeBase::DEFAULT))
     }
 
     /// The duration of the open Format
-    pub fn duration(&self) -> Timestamp {
-        self.duration.to_timestamp(TimeBase::DEFAULT)
+    ///
+    /// Will return None if the value is not known
+    pub fn duration(&self) -> Option<Timestamp> {
+        self.duration
+            .map(|ticks| ticks.to_timestamp(TimeBase::DEFAULT))
     }
 
     /// The bit-rate of the open Format
@@ -64,6 +70,23 @@ impl FormatConfig<Input> {
     }
 }
 
+impl FormatConfig<Output> {
+    pub(crate) unsafe fn from_output_fmt_ctx(ctx: *mut AVFormatContext) -> Self {
+        FormatConfig {
+            // Output formats don't have any demuxing information so these
+            // are left empty
+            start_time: None,
+            duration: None,
+            bit_rate: None,
+
+            num_streams: (*ctx).nb_streams as usize,
+            num_chapters: (*ctx).nb_chapters as usize,
+
+            _io: PhantomData,
+        }
+    }
+}
+
 impl<T> FormatConfig<T> {
     /// How many streams are in the open Format
     pub fn num_streams(&self) -> usize {
@@ -3,17 +3,21 @@
 use std::{marker::PhantomData, ops::Deref};
 
 use ffav_sys::{
+    avcodec_parameters_alloc, avcodec_parameters_free, avcodec_parameters_from_context,
     AVCodecContext, AVCodecID, AVCodecParameters, AVMediaType, AVPixelFormat, AVSampleFormat,
     AVStream,
 };
 
-use crate::util::{
-    channels::ChannelLayout,
-    color::{ColorPrimary, PixelFormat},
-    marker::{Audio, Unknown, Video},
-    sampling::SampleFormat,
-    time::{FrameRate, SampleRate, TimeBase, TimeBaseTicks},
-    MediaType,
+use crate::{
+    error::{Error, Result},
+    util::{
+        channels::ChannelLayout,
+        color::{ColorPrimary, PixelFormat},
+        marker::{Audio, Unknown, Video},
+        sampling::SampleFormat,
+        time::{FrameRate, SampleRate, TimeBase, TimeBaseTicks, Timestamp},
+        MediaType,
+    },
 };
 
 /// Holds information about the static configuration of a stream object
@@ -27,9 +31,11 @@ pub struct StreamConfig<AV> {
     id: i32,
     index: usize,
     time_base: TimeBase,
-    start_time: TimeBaseTicks,
-    duration: TimeBaseTicks,
+    start_time: Option<TimeBaseTicks>,
+    duration: Option<TimeBaseTicks>,
     num_frames: u64,
+    avg_frame_rate: Option<FrameRate>,
+    real_frame_rate: Option<FrameRate>,
     codec_params: CodecParameters,
     _type: PhantomData<AV>,
 }
@@ -52,6 +58,22 @@ impl<AV> StreamConfig<AV> {
     pub fn stream_index(&self) -> usize {
         self.index
     }
+
+    /// The presentation time of the first frame of this stream
+    ///
+    /// Will return None if the value is not known
+    pub fn start_time(&self) -> Option<Timestamp> {
+        self.start_time
+            .map(|ticks| ticks.to_timestamp(self.time_base))
+    }
+
+    /// The duration of this stream
+    ///
+    /// Will return None if the value is not known
+    pub fn duration(&self) -> Option<Timestamp> {
+        self.duration
+            .map(|ticks| ticks.to_timestamp(self.time_base))
+    }
 }
 
 impl StreamConfig<Unknown> {
@@ -67,16 +89,59 @@ impl StreamConfig<Unknown> {
                     debug_assert!((*stream).index >= 0);
                     (*stream).index as usize
                 },
-                time_base: TimeBase::from_av_rational(&(*stream).time_base),
-                start_time: TimeBaseTicks::new((*stream).start_time as u64),
-                duration: TimeBaseTicks::new((*stream).duration as u64),
+                // Streams are given a time base when they are created, so
+                // this only falls back if it was explicitly cleared
+                time_base: TimeBase::from_av_rational(&(*stream).time_base)
+                    .unwrap_or(TimeBase::NEW_STREAM),
+                start_time: TimeBaseTicks::from_av_timestamp((*stream).start_time),
+                duration: TimeBaseTicks::from_av_timestamp((*stream).duration),
                 num_frames: (*stream).nb_frames as u64,
+                avg_frame_rate: FrameRate::from_av_rational(&(*stream).avg_frame_rate),
+                real_frame_rate: FrameRate::from_av_rational(&(*stream).r_frame_rate),
                 codec_params: CodecParameters::from_av_params((*stream).codecpar),
                 _type: PhantomData,
             }
         }
     }
 
+    /// Build a configuration from an opened codec context which isn't associated
+    /// with a stream, such as an encoder.
+    ///
+    /// # Safety
+    /// The `ctx` must be a valid `AVCodecContext`
+    pub(crate) unsafe fn from_codec_ctx(ctx: *mut AVCodecContext) -> Result<StreamConfig<Unknown>> {
+        let mut params = avcodec_parameters_alloc();
+
+        if params.is_null() {
+            return Err(Error::AllocationFailed("allocating codec parameters"));
+        }
+
+        let err = avcodec_parameters_from_context(params, ctx);
+
+        if err < 0 {
+            avcodec_parameters_free(&mut params);
+            return Err(Error::from_av_err("copying codec parameters", err));
+        }
+
+        let codec_params = CodecParameters::from_av_params(params);
+        avcodec_parameters_free(&mut params);
+
+        Ok(StreamConfig {
+            // There is no associated stream so there is no meaningful id or index
+            id: 0,
+            index: 0,
+            time_base: TimeBase::from_av_rational(&(*ctx).time_base)
+                .ok_or(Error::InvalidArguments("reading the codec time base"))?,
+            start_time: None,
+            duration: None,
+            num_frames: 0,
+            avg_frame_rate: FrameRate::from_av_rational(&(*ctx).framerate),
+            real_frame_rate: FrameRate::from_av_rational(&(*ctx).framerate),
+            codec_params,
+            _type: PhantomData,
+        })
+    }
+
     /// Try to convert a stream of unknown type to another stream type
     pub fn try_as_type<T: MediaType>(self) -> Option<StreamConfig<T>> {
         if self.codec_params.media_type == T::MEDIA_TYPE {
@@ -156,8 +221,20 @@ impl StreamConfig<Video> {
     }
 
     /// Get the average frame-rate of this stream
-    pub fn avg_frame_rate(&self) -> FrameRate {
-        unimplemented!()
+    ///
+    /// Will return None if the value is not known
+    pub fn avg_frame_rate(&self) -> Option<FrameRate> {
+        self.avg_frame_rate
+    }
+
+    /// Get the lowest frame-rate which can accurately represent all timestamps
+    /// of this stream
+    ///
+    /// This is a guess made by libav and will return None if it couldn't be
+    /// determined. For variable frame-rate streams it may be much higher than
+    /// the `avg_frame_rate()`.
+    pub fn r_frame_rate(&self) -> Option<FrameRate> {
+        self.real_frame_rate
     }
 }
 
@@ -168,6 +245,7 @@ impl StreamConfig<Video> {
 #[derive(Debug, Clone)]
 pub struct DecodedStreamConfig<AV> {
     base: StreamConfig<AV>,
+    frame_rate: Option<FrameRate>,
 }
 
 impl<AV> Deref for DecodedStreamConfig<AV> {
@@ -185,13 +263,31 @@ impl<AV> DecodedStreamConfig<AV> {
     /// Create a configuration for a stream after the codec has be initialized
     ///
     /// # Safety
-    /// The `_codec_ctx` must be a valid `AVCodecContext` that was
+    /// The `codec_ctx` must be a valid `AVCodecContext` that was
     /// initialized for the stream passed in `cfg`
     pub unsafe fn new(
         cfg: StreamConfig<AV>,
-        _codec_ctx: *mut AVCodecContext,
+        codec_ctx: *mut AVCodecContext,
     ) -> DecodedStreamConfig<AV> {
-        DecodedStreamConfig { base: cfg }
+        // The codec may know the frame-rate from the bitstream, which is more
+        // reliable than the guesses made from the container
+        let frame_rate = FrameRate::from_av_rational(&(*codec_ctx).framerate)
+            .or(cfg.avg_frame_rate)
+            .or(cfg.real_frame_rate);
+
+        DecodedStreamConfig {
+            base: cfg,
+            frame_rate,
+        }
+    }
+}
+
+impl DecodedStreamConfig<Video> {
+    /// Get the frame-rate of the decoded frames
+    ///
+    /// Will return None if the value is not known
+    pub fn frame_rate(&self) -> Option<FrameRate> {
+        self.frame_rate
     }
 }
 
@@ -1,7 +1,7 @@
 use crate::{
     decode::simple::SimpleDecoder,
     error::Result,
-    raw::{format::Format, stream::Stream},
+    raw::{format::Format, scale::Scaler, stream::Stream},
     util::{
         color::ImageFormat,
         marker::{Input, Video},
@@ -12,10 +12,16 @@ use std::{marker::PhantomData, path::Path};
 
 /// Decode a video stream from the provided Format into a series of images
 ///
-/// The Images will be returned as an `image::ImageBuffer` of the selected type
+/// The Images will be returned as an `image::ImageBuffer` of the selected type,
+/// or an `image::DynamicImage` matching the pixel format of the stream.
+/// Decoded frames are converted to the `PixelFormat` of the image type when
+/// the stream uses a different format.
 pub struct ImageDecoder<I: ImageFormat> {
     simple: SimpleDecoder<Video>,
+    // Created once the pixel format of the decoded frames is known
+    scaler: Option<Scaler>,
     frame: Frame<Video>,
+    converted: Frame<Video>,
     _output: PhantomData<I>,
 }
 
@@ -55,10 +61,12 @@ impl<I: ImageFormat> ImageDecoder<I> {
         )?)
     }
 
-    fn inner_open(simple: SimpleDecoder<AV>) -> Result<ImageDecoder<I>> {
+    fn inner_open(simple: SimpleDecoder<Video>) -> Result<ImageDecoder<I>> {
         Ok(ImageDecoder {
             simple,
+            scaler: None,
             frame: Frame::new(),
+            converted: Frame::new(),
             _output: PhantomData,
         })
     }
@@ -67,9 +75,21 @@ impl<I: ImageFormat> ImageDecoder<I> {
     pub fn get_next_image(&mut self) -> Result<I> {
         self.simple.get_next_frame_into(&mut self.frame)?;
 
-        // TODO: Filter graph
+        let source = self.frame.pixel_format();
+        let target = I::pixel_format_for(source);
 
-        Ok(I::from_frame(&self.frame))
+        if source == target {
+            return Ok(I::from_frame(&self.frame));
+        }
+
+        // The scaler is only replaced if the stream changes format mid-way
+        let scaler = match &mut self.scaler {
+            Some(scaler) if scaler.pixel_format() == target => scaler,
+            scaler => scaler.insert(Scaler::new(target)),
+        };
+        scaler.scale(&self.frame, &mut self.converted)?;
+
+        Ok(I::from_frame(&self.converted))
     }
 
     /// Destroy the decoding context and return the contained input format
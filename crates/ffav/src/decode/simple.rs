@@ -1,10 +1,12 @@
 use std::path::Path;
 
 use crate::{
+    config::StreamConfig,
     error::{Error, Result},
     raw::{codec::Codec, format::Format, frame::Frame, packet::Packet, stream::Stream},
     util::{
         marker::{Decode, Input},
+        time::IntoStreamTimestamp,
         MediaType,
     },
 };
@@ -64,16 +66,11 @@ impl<AV: MediaType> SimpleDecoder<AV> {
 
     /// Get the next decoded frame from the stream
     pub fn get_next_frame(&mut self) -> Result<Frame<AV>> {
-        loop {
-            match self.codec.get_next_frame() {
-                Ok(f) => return Ok(f),
-                Err(Error::SubmitMoreInput) => {}
-                Err(x) => return Err(x),
-            }
+        let mut frame = Frame::new();
 
-            self.fmt.get_next_packet_into(&mut self.packet)?;
-            self.codec.submit_packet(&mut self.packet)?;
-        }
+        self.get_next_frame_into(&mut frame)?;
+
+        Ok(frame)
     }
 
     /// Get the next decoded frame from the stream and place it into the provided frame
@@ -84,15 +81,57 @@ impl<AV: MediaType> SimpleDecoder<AV> {
             match self.codec.get_next_frame_into(frame) {
                 Ok(_) => return Ok(()),
                 Err(Error::SubmitMoreInput) => {}
-                // NOTE: This will return Error::EoF for us at the end of the input
+                // NOTE: This will return Error::EoF for us once the decoder
+                // has been drained at the end of the input
+                Err(x) => return Err(x),
+            }
+
+            self.submit_next_packet()?;
+        }
+    }
+
+    /// Read packets until one for the decoded stream can be submitted
+    ///
+    /// At the end of the input the decoder is told to drain its buffered frames.
+    fn submit_next_packet(&mut self) -> Result<()> {
+        loop {
+            match self.fmt.get_next_packet_into(&mut self.packet) {
+                Ok(()) => {}
+                Err(Error::EoF) => return self.codec.send_eof(),
                 Err(x) => return Err(x),
             }
 
-            self.fmt.get_next_packet_into(&mut self.packet)?;
-            self.codec.submit_packet(&mut self.packet)?;
+            match self.codec.submit_packet(&mut self.packet) {
+                Ok(()) => return Ok(()),
+                // Skip packets from streams that aren't being decoded
+                Err(Error::PacketFromInvalidStream) => {}
+                Err(x) => return Err(x),
+            }
         }
     }
 
+    /// Seek to the nearest frame before the specified timestamp
+    ///
+    /// Any frames buffered in the decoder will be discarded
+    pub fn seek_to_nearest_frame_before(&mut self, ts: impl IntoStreamTimestamp<AV>) -> Result<()> {
+        let stream: &StreamConfig<AV> = self.codec.out_stream_config();
+        self.fmt.seek_stream_to_nearest_frame_before(stream, ts)?;
+        self.codec.flush();
+
+        Ok(())
+    }
+
+    /// Seek to the nearest frame after the specified timestamp
+    ///
+    /// Any frames buffered in the decoder will be discarded
+    pub fn seek_to_nearest_frame_after(&mut self, ts: impl IntoStreamTimestamp<AV>) -> Result<()> {
+        let stream: &StreamConfig<AV> = self.codec.out_stream_config();
+        self.fmt.seek_stream_to_nearest_frame_after(stream, ts)?;
+        self.codec.flush();
+
+        Ok(())
+    }
+
     /// Destroy the decoding context and return the contained input format
     ///
     /// The input format will be seeked back to the beginning of the file
@@ -61,6 +61,15 @@ pub enum Error {
     #[error("The name \"{0}\" could not be represented as a valid filter name.")]
     InvalidFilterName(String),
 
+    /// The filter name is already used in the FilterGraph
+    #[error("The filter name {0:?} is already used in the FilterGraph")]
+    DuplicateFilterName(String),
+
+    /// A command can't be queued for the filter as its name is also the type
+    /// of another filter in the FilterGraph
+    #[error("Commands queued for the filter {0:?} would also reach filters of that type")]
+    AmbiguousCommandTarget(String),
+
     /// The requested Filter output pad doesn't exist
     #[error("The filter {name:?} of type {filter_type:?} didn't have the requested output pad #{pad_number}")]
     OutputPadDoesntExist {
@@ -77,6 +86,14 @@ pub enum Error {
         pad_number: u32,
     },
 
+    /// The requested Filter output pad is already linked to another filter
+    #[error("The output pad #{pad_number} of the filter {name:?} is already linked")]
+    OutputPadAlreadyLinked { name: String, pad_number: u32 },
+
+    /// The requested Filter input pad is already linked to another filter
+    #[error("The input pad #{pad_number} of the filter {name:?} is already linked")]
+    InputPadAlreadyLinked { name: String, pad_number: u32 },
+
     /// The two pads requested to be linked don't have the same media type
     #[error("The requested pads do not have the same media type: src({src_type:?}) != dest({dest_type:?})")]
     PadTypeMismatch {
@@ -84,6 +101,60 @@ pub enum Error {
         dest_type: AVMediaType,
     },
 
+    /// A filter description left a labeled pad which wasn't connected to a filter
+    #[error("The filter description has an unconnected pad labeled {0:?}")]
+    UnlinkedFilterLabel(String),
+
+    /// The filter doesn't support the command that was sent to it
+    #[error("The filter {name:?} does not support the command {command:?}")]
+    FilterCommandNotSupported { name: String, command: String },
+
+    //
+    // The following errors can be generated while working with an output `Format`
+    //
+    /// Packets cannot be written to an output Format until its header has been written
+    #[error("The header of the output Format has not been written")]
+    HeaderNotWritten,
+
+    /// The output Format cannot be reconfigured once its header has been written
+    #[error("The header of the output Format has already been written")]
+    HeaderAlreadyWritten,
+
+    /// The Format does not contain a stream with the requested index
+    #[error("The Format does not contain a stream with index {0}")]
+    InvalidStreamIndex(usize),
+
+    /// The muxer needed to seek in an output which was opened without seeking support
+    #[error("The output Format requires seeking but its writer cannot seek")]
+    OutputNotSeekable,
+
+    /// The requested writer type is not the type the output Format was opened with
+    #[error("The output Format was not opened with a writer of the requested type")]
+    WriterTypeMismatch,
+
+    /// The requested codec does not handle the expected media type
+    #[error("The requested codec handles {found:?} but {expected:?} was expected")]
+    CodecTypeMismatch {
+        expected: AVMediaType,
+        found: AVMediaType,
+    },
+
+    /// The frame-rate of a video stream is required but isn't known
+    #[error("The frame-rate of the video stream is not known")]
+    UnknownFrameRate,
+
+    /// The sample rate of an audio stream is required but isn't known
+    #[error("The sample rate of the audio stream is not known")]
+    UnknownSampleRate,
+
+    /// The frame doesn't match the configuration it is being processed with
+    #[error("The supplied frame does not match the expected audio/video configuration")]
+    FrameConfigMismatch,
+
+    /// The frame's format doesn't store any data in the requested plane
+    #[error("The frame does not have a data plane #{0}")]
+    PlaneDoesntExist(usize),
+
     /// The provided packet was not for the stream that configured this Codec
     #[error("The supplied packet was not for the stream which configured this Codec")]
     PacketFromInvalidStream,
@@ -50,6 +50,8 @@ pub mod config;
 pub mod decode;
 pub mod error;
 pub mod raw;
+pub mod remux;
+pub mod transcode;
 pub mod util;
 
 pub use raw::{frame::Frame, packet::Packet};
@@ -1,17 +1,28 @@
 //! Structures related to handling codec objects
 
-use std::marker::PhantomData;
+use std::{ffi::CString, marker::PhantomData};
 
 use ffav_sys::{
-    avcodec_alloc_context3, avcodec_find_decoder, avcodec_free_context, avcodec_open2,
-    avcodec_parameters_to_context, avcodec_receive_frame, avcodec_send_packet, err::av_err,
-    AVCodecContext,
+    av_get_default_channel_layout, avcodec_alloc_context3, avcodec_find_decoder,
+    avcodec_find_encoder, avcodec_find_encoder_by_name, avcodec_flush_buffers,
+    avcodec_free_context, avcodec_open2, avcodec_parameters_to_context, avcodec_receive_frame,
+    avcodec_receive_packet, avcodec_send_frame, avcodec_send_packet, err::av_err, AVCodec,
+    AVCodecContext, AVCodecID, AVMediaType, AVPixelFormat, AVRational, AVSampleFormat,
+    AV_CODEC_FLAG_GLOBAL_HEADER,
 };
 
 use crate::{
-    config::stream::DecodedStreamConfig,
+    config::{stream::DecodedStreamConfig, Dictionary, StreamConfig},
     error::{Error, Result},
-    util::{marker::Decode, MediaType},
+    raw::filter::OutputConfig,
+    util::{
+        channels::ChannelLayout,
+        color::PixelFormat,
+        marker::{Audio, Decode, Encode, Video},
+        sampling::SampleFormat,
+        time::{FrameRate, SampleRate, TimeBase},
+        MediaType,
+    },
 };
 
 use super::{frame::Frame, packet::Packet, stream::Stream};
@@ -20,9 +31,11 @@ use super::{frame::Frame, packet::Packet, stream::Stream};
 ///
 /// The parameter `EnDec` can either be `crate::tags::Encode` or `crate::tags::Decode`
 /// The parameter `AV` indicates the type of frame produced by the decoding
-/// process.
+/// process, or consumed by the encoding process.
 pub struct Codec<EnDec, AV> {
     codec: *mut AVCodecContext,
+    // The configuration of the raw (decoded) side of the codec, for decoders
+    // this describes the frames produced and for encoders the frames accepted
     stream_config: DecodedStreamConfig<AV>,
     _codec: PhantomData<(EnDec, AV)>,
 }
@@ -99,6 +112,33 @@ impl<AV: MediaType> Codec<Decode, AV> {
         Ok(())
     }
 
+    /// Signal that no more packets will be submitted to the decoder
+    ///
+    /// Decoders may buffer several frames internally (eg. for B-frame reordering),
+    /// after this call the remaining frames can be retrieved with `get_next_frame()`
+    /// until it returns `Error::EoF`. Use `flush()` to decode more packets afterwards.
+    pub fn send_eof(&mut self) -> Result<()> {
+        unsafe {
+            let err = avcodec_send_packet(self.codec, std::ptr::null());
+
+            if err < 0 {
+                return Err(Error::from_av_err("draining decoder", err));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Discard all data buffered in the decoder
+    ///
+    /// This should be called after seeking the Format which packets are read
+    /// from, or to reset the decoder after `send_eof()`.
+    pub fn flush(&mut self) {
+        unsafe {
+            avcodec_flush_buffers(self.codec);
+        }
+    }
+
     /// Get the next decoded frame and put it into the provided frame object
     ///
     /// Any existing data contained in the frame will be unreferenced by this
@@ -133,6 +173,112 @@ impl<AV: MediaType> Codec<Decode, AV> {
     }
 }
 
+impl<AV: MediaType> Codec<Encode, AV> {
+    /// Start configuring an encoder for the codec with the provided id
+    ///
+    /// The default encoder for that codec will be used.
+    pub fn encoder_by_id(id: AVCodecID) -> Result<EncoderBuilder<AV>> {
+        unsafe { EncoderBuilder::new(avcodec_find_encoder(id)) }
+    }
+
+    /// Start configuring an encoder by its name
+    ///
+    /// The name is the same as would be passed to the ffmpeg CLI (eg. "libx264", "aac")
+    pub fn encoder_by_name(name: &str) -> Result<EncoderBuilder<AV>> {
+        let cname = CString::new(name)?;
+
+        unsafe { EncoderBuilder::new(avcodec_find_encoder_by_name(cname.as_ptr())) }
+    }
+
+    /// Submit a frame for encoding
+    ///
+    /// The frame's timestamp should be in the TimeBase of the encoder.
+    /// The supplied frame will be unreferenced by this operation and will be
+    /// available for later use.
+    pub fn submit_frame(&mut self, frame: &mut Frame<AV>) -> Result<()> {
+        unsafe {
+            let err = avcodec_send_frame(self.codec, frame.as_raw());
+
+            if err == av_err(ffav_sys::err::EAGAIN) {
+                return Err(Error::CouldNotAcceptInput);
+            } else if err < 0 {
+                return Err(Error::from_av_err("submitting frame to encoder", err));
+            }
+        }
+
+        // `avcodec_send_frame()` takes its own reference to the frame data so
+        // it is safe to unreference it here.
+        frame.unref();
+
+        Ok(())
+    }
+
+    /// Signal that no more frames will be submitted to the encoder
+    ///
+    /// The remaining buffered packets can then be retrieved with `get_next_packet()`
+    /// until it returns `Error::EoF`.
+    pub fn send_eof(&mut self) -> Result<()> {
+        unsafe {
+            let err = avcodec_send_frame(self.codec, std::ptr::null());
+
+            if err < 0 {
+                return Err(Error::from_av_err("draining encoder", err));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the next encoded packet and put it into the provided packet object
+    ///
+    /// Any existing data contained in the packet will be unreferenced by this
+    /// function. The packet's timestamps will be in the TimeBase of the encoder.
+    pub fn get_next_packet_into(&mut self, packet: &mut Packet) -> Result<()> {
+        unsafe {
+            let err = avcodec_receive_packet(self.codec, packet.as_raw());
+
+            if err == av_err(ffav_sys::err::EAGAIN) {
+                return Err(Error::SubmitMoreInput);
+            } else if err < 0 {
+                return Err(Error::from_av_err("receiving packet from encoder", err));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the next encoded packet and return a new packet object
+    ///
+    /// Panics: If a new packet cannot be allocated
+    pub fn get_next_packet(&mut self) -> Result<Packet> {
+        let mut packet = Packet::new();
+
+        self.get_next_packet_into(&mut packet)?;
+
+        Ok(packet)
+    }
+
+    /// Get the configuration of the frames accepted by this encoder
+    pub fn in_stream_config(&self) -> &DecodedStreamConfig<AV> {
+        &self.stream_config
+    }
+}
+
+impl Codec<Encode, Audio> {
+    /// The number of samples each submitted frame must contain
+    ///
+    /// Returns None if the encoder accepts frames of any size, otherwise every
+    /// frame except the last must contain exactly this many samples.
+    pub fn frame_size(&self) -> Option<u32> {
+        unsafe {
+            match (*self.codec).frame_size {
+                size if size > 0 => Some(size as u32),
+                _ => None,
+            }
+        }
+    }
+}
+
 impl<EnDec, AV> Codec<EnDec, AV> {
     /// Get the raw AVCdecContext pointer
     ///
@@ -161,3 +307,404 @@ impl<EnDec, AV> std::ops::Drop for Codec<EnDec, AV> {
 // no interior mutability.
 unsafe impl<EnDec, AV> std::marker::Send for Codec<EnDec, AV> {}
 unsafe impl<EnDec, AV> std::marker::Sync for Codec<EnDec, AV> {}
+
+/// Configuration for an encoder which has not been opened yet
+///
+/// Created by `Codec::encoder_by_id()` or `Codec::encoder_by_name()`, the
+/// available settings depend on the media type `AV` of the encoder.
+pub struct EncoderBuilder<AV> {
+    codec: *mut AVCodecContext,
+    encoder: *const AVCodec,
+    options: Dictionary,
+    _av: PhantomData<AV>,
+}
+
+impl<AV: MediaType> EncoderBuilder<AV> {
+    unsafe fn new(encoder: *const AVCodec) -> Result<EncoderBuilder<AV>> {
+        if encoder.is_null() {
+            return Err(Error::ResourceNotFound("encoder"));
+        }
+
+        if (*encoder).type_ != AV::MEDIA_TYPE {
+            return Err(Error::CodecTypeMismatch {
+                expected: AV::MEDIA_TYPE,
+                found: (*encoder).type_,
+            });
+        }
+
+        let codec = avcodec_alloc_context3(encoder);
+
+        if codec.is_null() {
+            return Err(Error::AllocationFailed("initializing encoder context"));
+        }
+
+        Ok(EncoderBuilder {
+            codec,
+            encoder,
+            options: Dictionary::new(),
+            _av: PhantomData,
+        })
+    }
+
+    /// Set the TimeBase of the timestamps of submitted frames and produced packets
+    pub fn time_base(self, time_base: TimeBase) -> Self {
+        unsafe {
+            (*self.codec).time_base = time_base.as_av_rational();
+        }
+        self
+    }
+
+    /// Get the configured TimeBase, None if it hasn't been set
+    pub(crate) fn configured_time_base(&self) -> Option<TimeBase> {
+        unsafe { TimeBase::from_av_rational(&(*self.codec).time_base) }
+    }
+
+    /// Set the target average bit-rate in bits per second
+    pub fn bit_rate(self, bit_rate: u64) -> Self {
+        unsafe {
+            (*self.codec).bit_rate = bit_rate as i64;
+        }
+        self
+    }
+
+    /// Place global headers in the codec extradata instead of every keyframe
+    ///
+    /// This is required by some output Formats, check `Format::needs_global_header()`
+    pub fn global_header(self, global: bool) -> Self {
+        unsafe {
+            if global {
+                (*self.codec).flags |= AV_CODEC_FLAG_GLOBAL_HEADER;
+            } else {
+                (*self.codec).flags &= !AV_CODEC_FLAG_GLOBAL_HEADER;
+            }
+        }
+        self
+    }
+
+    /// Set a private option of the encoder (eg. "preset" or "crf" for libx264)
+    pub fn option(mut self, key: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        self.options.add(key, value);
+        self
+    }
+
+    /// Replace all private options of the encoder with the provided Dictionary
+    pub fn options(mut self, options: Dictionary) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Open the encoder with the current configuration
+    pub fn open(mut self) -> Result<Codec<Encode, AV>> {
+        unsafe {
+            // Audio encoders conventionally count time in samples, so default
+            // to that if the user didn't specify a time base
+            if (*self.codec).time_base.num == 0 && (*self.codec).sample_rate > 0 {
+                (*self.codec).time_base = AVRational {
+                    num: 1,
+                    den: (*self.codec).sample_rate,
+                };
+            }
+
+            // Likewise video encoders count time in frames, but without a
+            // frame-rate there is no sensible default
+            if AV::MEDIA_TYPE == AVMediaType::AVMEDIA_TYPE_VIDEO && (*self.codec).time_base.num == 0
+            {
+                let frame_rate = FrameRate::from_av_rational(&(*self.codec).framerate).ok_or(
+                    Error::InvalidArguments(
+                        "opening video encoder without a time base or frame-rate",
+                    ),
+                )?;
+
+                (*self.codec).time_base = frame_rate.frame_time_base().as_av_rational();
+            }
+
+            let err = avcodec_open2(self.codec, self.encoder, self.options.as_dict());
+
+            if err < 0 {
+                return Err(Error::from_av_err("opening encoder", err));
+            }
+
+            let cfg = match StreamConfig::from_codec_ctx(self.codec)?.try_as_type::<AV>() {
+                Some(c) => c,
+                // SAFETY: The encoder was checked to be of type AV when the
+                // builder was created
+                None => unreachable!(),
+            };
+            let stream_config = DecodedStreamConfig::new(cfg, self.codec);
+
+            // Take ownership of the context so it isn't freed with the builder
+            let codec = std::mem::replace(&mut self.codec, std::ptr::null_mut());
+
+            Ok(Codec {
+                codec,
+                stream_config,
+                _codec: PhantomData,
+            })
+        }
+    }
+}
+
+impl EncoderBuilder<Video> {
+    /// Set the width of encoded frames
+    pub fn width(self, width: u32) -> Self {
+        unsafe {
+            (*self.codec).width = width as i32;
+        }
+        self
+    }
+
+    /// Set the height of encoded frames
+    pub fn height(self, height: u32) -> Self {
+        unsafe {
+            (*self.codec).height = height as i32;
+        }
+        self
+    }
+
+    /// Set the pixel format of submitted frames
+    pub fn pixel_format(self, format: PixelFormat) -> Self {
+        unsafe {
+            (*self.codec).pix_fmt = AVPixelFormat::from(format);
+        }
+        self
+    }
+
+    /// Set the frame-rate of submitted frames
+    ///
+    /// If no time base is set the encoder will use the duration of one frame
+    pub fn frame_rate(self, frame_rate: FrameRate) -> Self {
+        unsafe {
+            (*self.codec).framerate = frame_rate.as_av_rational();
+        }
+        self
+    }
+
+    /// Set the maximum number of frames between keyframes
+    pub fn gop_size(self, gop_size: u32) -> Self {
+        unsafe {
+            (*self.codec).gop_size = gop_size as i32;
+        }
+        self
+    }
+
+    /// Set the maximum number of consecutive B-frames
+    pub fn max_b_frames(self, max_b_frames: u32) -> Self {
+        unsafe {
+            (*self.codec).max_b_frames = max_b_frames as i32;
+        }
+        self
+    }
+
+    /// Configure the encoder to accept the frames produced by a FilterGraph
+    /// output
+    ///
+    /// The frame size, pixel format, sample aspect ratio and frame-rate are
+    /// set from the output's configuration. The time base is set to the
+    /// duration of one frame, or the time base of the output if the frame-rate
+    /// isn't known.
+    pub fn filter_output(self, output: &OutputConfig<Video>) -> Self {
+        let time_base = output
+            .frame_rate()
+            .map_or_else(|| output.time_base(), FrameRate::frame_time_base);
+
+        unsafe {
+            (*self.codec).time_base = time_base.as_av_rational();
+            (*self.codec).width = output.width() as i32;
+            (*self.codec).height = output.height() as i32;
+            (*self.codec).pix_fmt = AVPixelFormat::from(output.pixel_format());
+
+            if let Some((num, den)) = output.sample_aspect_ratio() {
+                (*self.codec).sample_aspect_ratio = AVRational {
+                    num: num as i32,
+                    den: den as i32,
+                };
+            }
+
+            if let Some(frame_rate) = output.frame_rate() {
+                (*self.codec).framerate = frame_rate.as_av_rational();
+            }
+        }
+        self
+    }
+
+    /// Get the frame size and pixel format of the frames the encoder will
+    /// accept as currently configured
+    pub(crate) fn frame_config(&self) -> (u32, u32, PixelFormat) {
+        unsafe {
+            (
+                (*self.codec).width.max(0) as u32,
+                (*self.codec).height.max(0) as u32,
+                PixelFormat::from((*self.codec).pix_fmt),
+            )
+        }
+    }
+
+    /// Fill in the settings which weren't configured from the decoder whose
+    /// frames will be encoded
+    pub(crate) fn defaults_from_decoder(self, decoder: &Codec<Decode, Video>) -> Result<Self> {
+        unsafe {
+            let enc = self.codec;
+            let dec = decoder.as_raw();
+
+            if (*enc).width == 0 {
+                (*enc).width = (*dec).width;
+            }
+
+            if (*enc).height == 0 {
+                (*enc).height = (*dec).height;
+            }
+
+            if (*enc).pix_fmt == AVPixelFormat::AV_PIX_FMT_NONE {
+                (*enc).pix_fmt = choose_supported(
+                    (*self.encoder).pix_fmts,
+                    (*dec).pix_fmt,
+                    AVPixelFormat::AV_PIX_FMT_NONE,
+                );
+            }
+
+            if (*enc).sample_aspect_ratio.num == 0 {
+                (*enc).sample_aspect_ratio = (*dec).sample_aspect_ratio;
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+impl EncoderBuilder<Audio> {
+    /// Set the sample rate of submitted frames
+    pub fn sample_rate(self, sample_rate: SampleRate) -> Self {
+        unsafe {
+            (*self.codec).sample_rate = sample_rate.as_hz() as i32;
+        }
+        self
+    }
+
+    /// Set the sample format of submitted frames
+    pub fn sample_format(self, format: SampleFormat) -> Self {
+        unsafe {
+            (*self.codec).sample_fmt = AVSampleFormat::from(format);
+        }
+        self
+    }
+
+    /// Set the channel layout of submitted frames
+    pub fn channel_layout(self, layout: ChannelLayout) -> Self {
+        unsafe {
+            (*self.codec).channel_layout = layout.bits();
+            (*self.codec).channels = layout.bits().count_ones() as i32;
+        }
+        self
+    }
+
+    /// Configure the encoder to accept the frames produced by a FilterGraph
+    /// output
+    ///
+    /// The sample rate, sample format and channel layout are set from the
+    /// output's configuration. The time base is set to the duration of one
+    /// sample.
+    pub fn filter_output(self, output: &OutputConfig<Audio>) -> Self {
+        unsafe {
+            (*self.codec).time_base = AVRational {
+                num: 1,
+                den: output.sample_rate().as_hz() as i32,
+            };
+        }
+
+        self.sample_rate(output.sample_rate())
+            .sample_format(output.sample_format())
+            .channel_layout(output.channel_layout())
+    }
+
+    /// Get the sample rate, sample format and channel layout of the frames the
+    /// encoder will accept as currently configured
+    pub(crate) fn sample_config(&self) -> (SampleRate, SampleFormat, ChannelLayout) {
+        unsafe {
+            (
+                SampleRate::new((*self.codec).sample_rate.max(0) as u32),
+                SampleFormat::from((*self.codec).sample_fmt),
+                ChannelLayout::from_bits_truncate((*self.codec).channel_layout),
+            )
+        }
+    }
+
+    /// Fill in the settings which weren't configured from the decoder whose
+    /// frames will be encoded
+    ///
+    /// Where the encoder doesn't support the decoded value the first value
+    /// it does support is used instead.
+    pub(crate) fn defaults_from_decoder(self, decoder: &Codec<Decode, Audio>) -> Result<Self> {
+        unsafe {
+            let enc = self.codec;
+            let dec = decoder.as_raw();
+
+            if (*enc).sample_rate == 0 {
+                (*enc).sample_rate =
+                    choose_supported((*self.encoder).supported_samplerates, (*dec).sample_rate, 0);
+            }
+
+            if (*enc).sample_fmt == AVSampleFormat::AV_SAMPLE_FMT_NONE {
+                (*enc).sample_fmt = choose_supported(
+                    (*self.encoder).sample_fmts,
+                    (*dec).sample_fmt,
+                    AVSampleFormat::AV_SAMPLE_FMT_NONE,
+                );
+            }
+
+            if (*enc).channel_layout == 0 {
+                // Some decoders only report a channel count
+                let layout = match (*dec).channel_layout {
+                    0 => av_get_default_channel_layout((*dec).channels) as u64,
+                    layout => layout,
+                };
+
+                (*enc).channel_layout =
+                    choose_supported((*self.encoder).channel_layouts, layout, 0);
+                (*enc).channels = (*enc).channel_layout.count_ones() as i32;
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+impl<AV> std::ops::Drop for EncoderBuilder<AV> {
+    fn drop(&mut self) {
+        unsafe {
+            // If the builder was opened the context was moved out and this is NULL
+            // which `avcodec_free_context` handles for us
+            avcodec_free_context(&mut self.codec);
+        }
+    }
+}
+
+/// Pick `preferred` if it is in the list of `supported` values, otherwise the
+/// first supported value
+///
+/// # Safety
+/// `supported` must either be NULL, meaning any value is supported, or point
+/// to a list ending with `terminator`
+unsafe fn choose_supported<T: Copy + PartialEq>(
+    supported: *const T,
+    preferred: T,
+    terminator: T,
+) -> T {
+    if supported.is_null() || *supported == terminator {
+        return preferred;
+    }
+
+    let mut current = supported;
+    while *current != terminator {
+        if *current == preferred {
+            return preferred;
+        }
+        current = current.add(1);
+    }
+
+    *supported
+}
+
+// SAFTEY: The EncoderBuilder type is the sole owner of the contained context
+// pointer, the encoder pointer refers to static data owned by libav.
+unsafe impl<AV> std::marker::Send for EncoderBuilder<AV> {}
+unsafe impl<AV> std::marker::Sync for EncoderBuilder<AV> {}
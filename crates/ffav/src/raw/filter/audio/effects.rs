@@ -0,0 +1,144 @@
+use std::ffi::CString;
+
+use crate::{
+    config::Dictionary,
+    raw::filter::{Filter, HasInputPads, HasOutputPads, UpdatableFilter},
+    util::time::Timestamp,
+};
+
+/// Change the speed of the audio without changing its pitch
+pub struct ATempo {
+    name: String,
+    tempo: f64,
+}
+
+impl ATempo {
+    /// Play the audio `tempo` times faster, a tempo below 1.0 slows it down
+    ///
+    /// The tempo must be between 0.5 and 100.0.
+    pub fn new(name: impl Into<String>, tempo: f64) -> Self {
+        ATempo {
+            name: name.into(),
+            tempo,
+        }
+    }
+}
+
+impl HasInputPads for ATempo {}
+impl HasOutputPads for ATempo {}
+
+impl Filter for ATempo {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("atempo").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("tempo", format!("{}", self.tempo));
+
+        dict
+    }
+}
+
+impl UpdatableFilter for ATempo {
+    /// The new tempo
+    type Command = f64;
+
+    fn command_args(tempo: &f64) -> (String, String) {
+        ("tempo".into(), format!("{}", tempo))
+    }
+}
+
+/// A single reflection added by `AEcho`
+#[derive(Debug, Clone, Copy)]
+pub struct Echo {
+    /// The delay from the original sound
+    pub delay: Timestamp,
+    /// The volume of the echo relative to the original, between 0.0 and 1.0
+    pub decay: f64,
+}
+
+impl Echo {
+    pub fn new(delay: impl Into<Timestamp>, decay: f64) -> Self {
+        Echo {
+            delay: delay.into(),
+            decay,
+        }
+    }
+}
+
+/// Add echoes to the audio
+pub struct AEcho {
+    name: String,
+    in_gain: f64,
+    out_gain: f64,
+    echoes: Vec<Echo>,
+}
+
+impl AEcho {
+    /// Add `echoes` to the audio, at least one echo must be given
+    ///
+    /// `in_gain` is applied to the input before the echoes are added and
+    /// `out_gain` to the result.
+    ///
+    /// # Panics
+    /// If `echoes` is empty
+    pub fn new(name: impl Into<String>, in_gain: f64, out_gain: f64, echoes: Vec<Echo>) -> Self {
+        assert!(!echoes.is_empty(), "AEcho requires at least one echo");
+
+        AEcho {
+            name: name.into(),
+            in_gain,
+            out_gain,
+            echoes,
+        }
+    }
+}
+
+impl HasInputPads for AEcho {}
+impl HasOutputPads for AEcho {}
+
+impl Filter for AEcho {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("aecho").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("in_gain", format!("{}", self.in_gain));
+        dict.add("out_gain", format!("{}", self.out_gain));
+
+        // Delays are given in milliseconds
+        let delays: Vec<_> = self
+            .echoes
+            .iter()
+            .map(|echo| format!("{}", echo.delay.as_secs_f64() * 1000.0))
+            .collect();
+        let decays: Vec<_> = self
+            .echoes
+            .iter()
+            .map(|echo| format!("{}", echo.decay))
+            .collect();
+
+        dict.add("delays", delays.join("|"));
+        dict.add("decays", decays.join("|"));
+
+        dict
+    }
+}
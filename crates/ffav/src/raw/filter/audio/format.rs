@@ -3,7 +3,7 @@ use std::ffi::CString;
 use crate::{
     config::Dictionary,
     raw::filter::{Filter, HasInputPads, HasOutputPads},
-    util::{channels::ChannelLayout, sampling::SampleFormat},
+    util::{channels::ChannelLayout, sampling::SampleFormat, time::SampleRate},
 };
 
 pub struct AFormat {
@@ -62,3 +62,55 @@ impl Filter for AFormat {
         dict
     }
 }
+
+/// Resample the audio to a different sample rate
+pub struct AResample {
+    name: String,
+    sample_rate: SampleRate,
+    compensation: Option<u32>,
+}
+
+impl AResample {
+    pub fn new(name: impl Into<String>, sample_rate: SampleRate) -> Self {
+        AResample {
+            name: name.into(),
+            sample_rate,
+            compensation: None,
+        }
+    }
+
+    /// Stretch, squeeze, pad or trim the audio by at most `samples_per_second`
+    /// to keep it in sync with its timestamps
+    pub fn timestamp_compensation(mut self, samples_per_second: u32) -> Self {
+        self.compensation = Some(samples_per_second);
+        self
+    }
+}
+
+impl HasInputPads for AResample {}
+impl HasOutputPads for AResample {}
+
+impl Filter for AResample {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("aresample").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("out_sample_rate", format!("{}", self.sample_rate.as_hz()));
+
+        if let Some(samples_per_second) = self.compensation {
+            dict.add("async", format!("{}", samples_per_second));
+        }
+
+        dict
+    }
+}
@@ -0,0 +1,219 @@
+use std::ffi::CString;
+
+use crate::{
+    config::Dictionary,
+    raw::filter::{Filter, HasInputPads, HasOutputPads, UpdatableFilter},
+    util::time::Timestamp,
+};
+
+/// Change the volume of the audio
+pub struct Volume {
+    name: String,
+    volume: VolumeLevel,
+}
+
+/// A volume level for the `Volume` filter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VolumeLevel {
+    /// A linear gain applied to each sample, 1.0 leaves the audio unchanged
+    Linear(f64),
+    /// A gain in decibels, 0.0 leaves the audio unchanged
+    Decibels(f64),
+}
+
+impl VolumeLevel {
+    fn as_arg(self) -> String {
+        match self {
+            VolumeLevel::Linear(gain) => format!("{}", gain),
+            VolumeLevel::Decibels(db) => format!("{}dB", db),
+        }
+    }
+}
+
+impl Volume {
+    pub fn new(name: impl Into<String>, volume: VolumeLevel) -> Self {
+        Volume {
+            name: name.into(),
+            volume,
+        }
+    }
+}
+
+impl HasInputPads for Volume {}
+impl HasOutputPads for Volume {}
+
+impl Filter for Volume {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("volume").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("volume", self.volume.as_arg());
+
+        dict
+    }
+}
+
+impl UpdatableFilter for Volume {
+    type Command = VolumeLevel;
+
+    fn command_args(command: &VolumeLevel) -> (String, String) {
+        ("volume".into(), command.as_arg())
+    }
+}
+
+/// Normalize the perceived loudness of the audio using EBU R128
+///
+/// The filter resamples the audio to 192kHz internally, an `AResample` or
+/// `AFormat` filter should normally follow it.
+pub struct LoudNorm {
+    name: String,
+    integrated: f64,
+    true_peak: f64,
+    loudness_range: f64,
+    linear: bool,
+}
+
+impl LoudNorm {
+    /// Create a filter with the libav default targets, an integrated loudness
+    /// of -24 LUFS, a true peak of -2 dBTP and a loudness range of 7 LU
+    pub fn new(name: impl Into<String>) -> Self {
+        LoudNorm {
+            name: name.into(),
+            integrated: -24.0,
+            true_peak: -2.0,
+            loudness_range: 7.0,
+            linear: true,
+        }
+    }
+
+    /// Set the target integrated loudness in LUFS, between -70.0 and -5.0
+    pub fn integrated(mut self, lufs: f64) -> Self {
+        self.integrated = lufs;
+        self
+    }
+
+    /// Set the maximum true peak in dBTP, between -9.0 and 0.0
+    pub fn true_peak(mut self, dbtp: f64) -> Self {
+        self.true_peak = dbtp;
+        self
+    }
+
+    /// Set the target loudness range in LU, between 1.0 and 20.0
+    pub fn loudness_range(mut self, lu: f64) -> Self {
+        self.loudness_range = lu;
+        self
+    }
+
+    /// Only allow dynamic normalization, which adjusts the gain over time
+    /// rather than applying a single linear gain
+    pub fn dynamic(mut self) -> Self {
+        self.linear = false;
+        self
+    }
+}
+
+impl HasInputPads for LoudNorm {}
+impl HasOutputPads for LoudNorm {}
+
+impl Filter for LoudNorm {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("loudnorm").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("I", format!("{}", self.integrated));
+        dict.add("TP", format!("{}", self.true_peak));
+        dict.add("LRA", format!("{}", self.loudness_range));
+        dict.add("linear", if self.linear { "true" } else { "false" });
+
+        dict
+    }
+}
+
+/// Remove silent sections of the audio
+///
+/// Audio is considered silent while it stays below a threshold for at least
+/// a minimum duration. By default nothing is removed.
+pub struct SilenceRemove {
+    name: String,
+    start: Option<(Timestamp, VolumeLevel)>,
+    stop: Option<(Timestamp, VolumeLevel)>,
+}
+
+impl SilenceRemove {
+    pub fn new(name: impl Into<String>) -> Self {
+        SilenceRemove {
+            name: name.into(),
+            start: None,
+            stop: None,
+        }
+    }
+
+    /// Remove the silence at the start of the audio
+    pub fn leading(mut self, min_duration: impl Into<Timestamp>, threshold: VolumeLevel) -> Self {
+        self.start = Some((min_duration.into(), threshold));
+        self
+    }
+
+    /// Remove every silent section after the start of the audio, including
+    /// the silence at the end
+    pub fn all(mut self, min_duration: impl Into<Timestamp>, threshold: VolumeLevel) -> Self {
+        self.stop = Some((min_duration.into(), threshold));
+        self
+    }
+}
+
+impl HasInputPads for SilenceRemove {}
+impl HasOutputPads for SilenceRemove {}
+
+impl Filter for SilenceRemove {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("silenceremove").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        if let Some((duration, threshold)) = self.start {
+            dict.add("start_periods", "1");
+            dict.add("start_duration", format!("{}", duration));
+            dict.add("start_threshold", threshold.as_arg());
+        }
+
+        if let Some((duration, threshold)) = self.stop {
+            // A negative number of periods keeps removing silence after the
+            // first period rather than ending the output
+            dict.add("stop_periods", "-1");
+            dict.add("stop_duration", format!("{}", duration));
+            dict.add("stop_threshold", threshold.as_arg());
+        }
+
+        dict
+    }
+}
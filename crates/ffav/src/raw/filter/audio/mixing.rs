@@ -0,0 +1,249 @@
+use std::ffi::CString;
+
+use crate::{
+    config::Dictionary,
+    raw::filter::{Filter, HasInputPads, HasOutputPads},
+    util::{channels::ChannelLayout, time::Timestamp},
+};
+
+/// How the length of the `AMix` output is determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MixDuration {
+    /// End when the longest input ends
+    Longest,
+    /// End when the shortest input ends
+    Shortest,
+    /// End when the first input ends
+    First,
+}
+
+/// Mix several audio inputs into one
+///
+/// Input pads are numbered from 0, all inputs are mixed with equal weight
+/// unless set with `weights()`.
+pub struct AMix {
+    name: String,
+    inputs: u32,
+    duration: MixDuration,
+    dropout_transition: Option<Timestamp>,
+    weights: Option<Vec<f64>>,
+}
+
+impl AMix {
+    pub fn new(name: impl Into<String>, inputs: u32) -> Self {
+        AMix {
+            name: name.into(),
+            inputs,
+            duration: MixDuration::Longest,
+            dropout_transition: None,
+            weights: None,
+        }
+    }
+
+    pub fn duration(mut self, duration: MixDuration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Set how long it takes to renormalize the volume when an input ends
+    pub fn dropout_transition(mut self, transition: impl Into<Timestamp>) -> Self {
+        self.dropout_transition = Some(transition.into());
+        self
+    }
+
+    /// Set the weight of each input in pad order
+    pub fn weights(mut self, weights: Vec<f64>) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+}
+
+impl HasInputPads for AMix {}
+impl HasOutputPads for AMix {}
+
+impl Filter for AMix {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("amix").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("inputs", format!("{}", self.inputs));
+
+        let duration = match self.duration {
+            MixDuration::Longest => "longest",
+            MixDuration::Shortest => "shortest",
+            MixDuration::First => "first",
+        };
+        dict.add("duration", duration);
+
+        if let Some(transition) = self.dropout_transition {
+            dict.add(
+                "dropout_transition",
+                format!("{}", transition.as_secs_f64()),
+            );
+        }
+
+        if let Some(weights) = &self.weights {
+            let weights: Vec<_> = weights.iter().map(|w| format!("{}", w)).collect();
+            dict.add("weights", weights.join(" "));
+        }
+
+        dict
+    }
+}
+
+/// Combine the channels of several audio inputs into one multi-channel stream
+///
+/// Input pads are numbered from 0, the channels of each input are placed
+/// after the channels of the previous input.
+pub struct AMerge {
+    name: String,
+    inputs: u32,
+}
+
+impl AMerge {
+    pub fn new(name: impl Into<String>, inputs: u32) -> Self {
+        AMerge {
+            name: name.into(),
+            inputs,
+        }
+    }
+}
+
+impl HasInputPads for AMerge {}
+impl HasOutputPads for AMerge {}
+
+impl Filter for AMerge {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("amerge").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("inputs", format!("{}", self.inputs));
+
+        dict
+    }
+}
+
+/// Duplicate the audio to several output pads
+pub struct ASplit {
+    name: String,
+    outputs: u32,
+}
+
+impl ASplit {
+    /// Split the audio into `outputs` identical streams, output pads are
+    /// numbered from 0
+    pub fn new(name: impl Into<String>, outputs: u32) -> Self {
+        ASplit {
+            name: name.into(),
+            outputs,
+        }
+    }
+}
+
+impl HasInputPads for ASplit {}
+impl HasOutputPads for ASplit {}
+
+impl Filter for ASplit {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("asplit").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("outputs", format!("{}", self.outputs));
+
+        dict
+    }
+}
+
+/// Remix the channels of the audio with a gain matrix
+pub struct Pan {
+    name: String,
+    layout: ChannelLayout,
+    gains: Vec<Vec<f64>>,
+}
+
+impl Pan {
+    /// Remix the audio into the `layout` channel layout
+    ///
+    /// `gains` holds one row per output channel, each row holds the gain
+    /// applied to each input channel, in the channel order described by
+    /// `ChannelLayout`.
+    pub fn new(name: impl Into<String>, layout: ChannelLayout, gains: Vec<Vec<f64>>) -> Self {
+        Pan {
+            name: name.into(),
+            layout,
+            gains,
+        }
+    }
+}
+
+impl HasInputPads for Pan {}
+impl HasOutputPads for Pan {}
+
+impl Filter for Pan {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("pan").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        // The filter takes a single argument of the form
+        // `layout|c0=0.5*c0+0.5*c1|c1=...`
+        let mut args = format!("0x{:x}", self.layout);
+
+        for (out_channel, row) in self.gains.iter().enumerate() {
+            args.push_str(&format!("|c{}=", out_channel));
+
+            for (in_channel, gain) in row.iter().enumerate() {
+                if in_channel == 0 {
+                    args.push_str(&format!("{}*c{}", gain, in_channel));
+                } else if *gain < 0.0 {
+                    args.push_str(&format!("-{}*c{}", -gain, in_channel));
+                } else {
+                    args.push_str(&format!("+{}*c{}", gain, in_channel));
+                }
+            }
+        }
+
+        dict.add("args", args);
+
+        dict
+    }
+}
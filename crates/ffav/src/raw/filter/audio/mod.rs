@@ -3,11 +3,17 @@
 //! REF: https://ffmpeg.org/ffmpeg-filters.html#Audio-Filters
 
 mod buffersrc;
+mod effects;
 mod format;
+mod level;
+mod mixing;
 mod sequencing;
 mod sink;
 
 pub use buffersrc::ABufferSource;
-pub use format::AFormat;
+pub use effects::*;
+pub use format::{AFormat, AResample};
+pub use level::*;
+pub use mixing::*;
 pub use sequencing::*;
 pub use sink::*;
@@ -49,7 +49,7 @@ impl Filter for ATrim {
         &self.name
     }
 
-    fn filter_type_name() -> std::ffi::CString {
+    fn filter_type_name() -> CString {
         // NOTE: This unwrap should never fail because I know this string to be
         // a valid C-style string. If this fails something horrible has gone wrong
         // and a bug should probably be filed.
@@ -73,20 +73,89 @@ impl Filter for ATrim {
     }
 }
 
+/// The shape of the volume change in a fade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FadeCurve {
+    /// Linear, the default
+    #[default]
+    Triangular,
+    QuarterSine,
+    HalfSine,
+    ExponentialSine,
+    Logarithmic,
+    InvertedParabola,
+    Quadratic,
+    Cubic,
+    SquareRoot,
+    CubicRoot,
+    Parabola,
+    Exponential,
+    InvertedQuarterSine,
+    InvertedHalfSine,
+    DoubleExponentialSeat,
+    DoubleExponentialSigmoid,
+}
+
+impl FadeCurve {
+    fn as_arg(self) -> &'static str {
+        match self {
+            FadeCurve::Triangular => "tri",
+            FadeCurve::QuarterSine => "qsin",
+            FadeCurve::HalfSine => "hsin",
+            FadeCurve::ExponentialSine => "esin",
+            FadeCurve::Logarithmic => "log",
+            FadeCurve::InvertedParabola => "ipar",
+            FadeCurve::Quadratic => "qua",
+            FadeCurve::Cubic => "cub",
+            FadeCurve::SquareRoot => "squ",
+            FadeCurve::CubicRoot => "cbr",
+            FadeCurve::Parabola => "par",
+            FadeCurve::Exponential => "exp",
+            FadeCurve::InvertedQuarterSine => "iqsin",
+            FadeCurve::InvertedHalfSine => "ihsin",
+            FadeCurve::DoubleExponentialSeat => "dese",
+            FadeCurve::DoubleExponentialSigmoid => "desi",
+        }
+    }
+}
+
+/// Fade from the end of one audio input into the start of another
+///
+/// Input pad 0 is faded out and input pad 1 is faded in.
 pub struct ACrosssfade {
     name: String,
     duration: Timestamp,
     overlap: bool,
+    fade_out_curve: FadeCurve,
+    fade_in_curve: FadeCurve,
 }
 
 impl ACrosssfade {
-    pub fn no_overlap(name: impl Into<String>, duration: impl Into<Timestamp>) -> Self {
+    /// Overlap the end of the first input with the start of the second
+    pub fn new(name: impl Into<String>, duration: impl Into<Timestamp>) -> Self {
         ACrosssfade {
             name: name.into(),
             duration: duration.into(),
+            overlap: true,
+            fade_out_curve: FadeCurve::default(),
+            fade_in_curve: FadeCurve::default(),
+        }
+    }
+
+    /// Fade the first input out completely before fading in the second
+    pub fn no_overlap(name: impl Into<String>, duration: impl Into<Timestamp>) -> Self {
+        ACrosssfade {
             overlap: false,
+            ..Self::new(name, duration)
         }
     }
+
+    /// Set the curves used to fade out the first input and fade in the second
+    pub fn curves(mut self, fade_out: FadeCurve, fade_in: FadeCurve) -> Self {
+        self.fade_out_curve = fade_out;
+        self.fade_in_curve = fade_in;
+        self
+    }
 }
 
 impl HasInputPads for ACrosssfade {}
@@ -97,7 +166,7 @@ impl Filter for ACrosssfade {
         &self.name
     }
 
-    fn filter_type_name() -> std::ffi::CString {
+    fn filter_type_name() -> CString {
         // NOTE: This unwrap should never fail because I know this string to be
         // a valid C-style string. If this fails something horrible has gone wrong
         // and a bug should probably be filed.
@@ -109,6 +178,132 @@ impl Filter for ACrosssfade {
 
         dict.add("duration", format!("{}", self.duration));
         dict.add("overlap", format!("{}", if self.overlap { 1 } else { 0 }));
+        dict.add("curve1", self.fade_out_curve.as_arg());
+        dict.add("curve2", self.fade_in_curve.as_arg());
+
+        dict
+    }
+}
+
+/// Gradually fade the audio in or out
+pub struct AFade {
+    name: String,
+    fade_in: bool,
+    start: Timestamp,
+    duration: Timestamp,
+    curve: FadeCurve,
+}
+
+impl AFade {
+    /// Fade in from silence, the audio before `start` is silenced
+    pub fn fade_in(
+        name: impl Into<String>,
+        start: impl Into<Timestamp>,
+        duration: impl Into<Timestamp>,
+    ) -> Self {
+        AFade {
+            name: name.into(),
+            fade_in: true,
+            start: start.into(),
+            duration: duration.into(),
+            curve: FadeCurve::default(),
+        }
+    }
+
+    /// Fade out to silence, the audio after the fade is silenced
+    pub fn fade_out(
+        name: impl Into<String>,
+        start: impl Into<Timestamp>,
+        duration: impl Into<Timestamp>,
+    ) -> Self {
+        AFade {
+            fade_in: false,
+            ..Self::fade_in(name, start, duration)
+        }
+    }
+
+    pub fn curve(mut self, curve: FadeCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+}
+
+impl HasInputPads for AFade {}
+impl HasOutputPads for AFade {}
+
+impl Filter for AFade {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("afade").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("type", if self.fade_in { "in" } else { "out" });
+        dict.add("start_time", format!("{}", self.start));
+        dict.add("duration", format!("{}", self.duration));
+        dict.add("curve", self.curve.as_arg());
+
+        dict
+    }
+}
+
+/// Delay the audio of each channel
+///
+/// The delay is filled with silence.
+pub struct ADelay {
+    name: String,
+    delays: Vec<Timestamp>,
+}
+
+impl ADelay {
+    /// Delay each channel by the matching entry in `delays`, channels without
+    /// an entry aren't delayed
+    ///
+    /// # Panics
+    /// If `delays` is empty
+    pub fn new(name: impl Into<String>, delays: Vec<Timestamp>) -> Self {
+        assert!(!delays.is_empty(), "ADelay requires at least one delay");
+
+        ADelay {
+            name: name.into(),
+            delays,
+        }
+    }
+}
+
+impl HasInputPads for ADelay {}
+impl HasOutputPads for ADelay {}
+
+impl Filter for ADelay {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("adelay").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        // Delays are given in milliseconds
+        let delays: Vec<_> = self
+            .delays
+            .iter()
+            .map(|delay| format!("{}", delay.as_secs_f64() * 1000.0))
+            .collect();
+        dict.add("delays", delays.join("|"));
 
         dict
     }
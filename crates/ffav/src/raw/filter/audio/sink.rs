@@ -1,19 +1,49 @@
 use crate::{
     config::Dictionary,
     error::{Error, Result},
-    raw::filter::{Filter, FilterOutput, HasInputPads},
-    util::marker::Audio,
+    raw::filter::{binary_option_arg, Filter, FilterOutput, HasInputPads},
+    util::{channels::ChannelLayout, marker::Audio, sampling::SampleFormat, time::SampleRate},
 };
-use ffav_sys::av_buffersink_get_frame;
+use ffav_sys::{av_buffersink_get_frame, AVSampleFormat};
 use std::ffi::CString;
 
+/// An output of the FilterGraph which audio frames can be retrieved from
+///
+/// The FilterGraph will convert the audio to match the constraints set on the
+/// sink when it is configured. By default any audio is accepted.
 pub struct ABufferSink {
     name: String,
+    sample_formats: Vec<SampleFormat>,
+    sample_rates: Vec<SampleRate>,
+    channel_layouts: Vec<ChannelLayout>,
 }
 
 impl ABufferSink {
     pub fn new(name: impl Into<String>) -> Self {
-        ABufferSink { name: name.into() }
+        ABufferSink {
+            name: name.into(),
+            sample_formats: Vec::new(),
+            sample_rates: Vec::new(),
+            channel_layouts: Vec::new(),
+        }
+    }
+
+    /// Only accept frames in one of the sample `formats`
+    pub fn sample_formats(mut self, formats: Vec<SampleFormat>) -> Self {
+        self.sample_formats = formats;
+        self
+    }
+
+    /// Only accept frames with one of the sample `rates`
+    pub fn sample_rates(mut self, rates: Vec<SampleRate>) -> Self {
+        self.sample_rates = rates;
+        self
+    }
+
+    /// Only accept frames with one of the channel `layouts`
+    pub fn channel_layouts(mut self, layouts: Vec<ChannelLayout>) -> Self {
+        self.channel_layouts = layouts;
+        self
     }
 }
 
@@ -30,7 +60,33 @@ impl Filter for ABufferSink {
     }
 
     fn config_parameters_dict(&self) -> Dictionary {
-        Dictionary::new()
+        let mut dict = Dictionary::new();
+
+        if !self.sample_formats.is_empty() {
+            let formats = self
+                .sample_formats
+                .iter()
+                .map(|&format| (AVSampleFormat::from(format) as i32).to_ne_bytes());
+            dict.add("sample_fmts", binary_option_arg(formats));
+        }
+
+        if !self.sample_rates.is_empty() {
+            let rates = self
+                .sample_rates
+                .iter()
+                .map(|rate| (rate.as_hz() as i32).to_ne_bytes());
+            dict.add("sample_rates", binary_option_arg(rates));
+        }
+
+        if !self.channel_layouts.is_empty() {
+            let layouts = self
+                .channel_layouts
+                .iter()
+                .map(|layout| layout.bits().to_ne_bytes());
+            dict.add("channel_layouts", binary_option_arg(layouts));
+        }
+
+        dict
     }
 }
 
@@ -53,10 +109,20 @@ impl FilterOutput for ABufferSink {
     }
 }
 
+/// Discards all audio it receives
+///
+/// Useful to terminate outputs of a filter which aren't needed, eg. a branch
+/// of an `ASplit`. It is added to the graph with `FilterGraph::add()`.
 pub struct ANullSink {
     name: String,
 }
 
+impl ANullSink {
+    pub fn new(name: impl Into<String>) -> Self {
+        ANullSink { name: name.into() }
+    }
+}
+
 impl Filter for ANullSink {
     fn filter_type_name() -> CString {
         // NOTE: This unwrap should never fail because I know this string to be
@@ -1,29 +1,73 @@
 use super::{
     AbstractHandle, Filter, FilterHandle, FilterInput, FilterOutput, Handle, HasInputPads,
-    HasOutputPads, InputHandle, OutputHandle,
+    HasOutputPads, InputHandle, OutputConfig, OutputHandle, UpdatableFilter,
 };
 use crate::{
     config::Dictionary,
     error::{Error, Result},
-    util::marker::{Configured, Unconfigured},
+    util::{
+        marker::{Audio, Configured, Unconfigured},
+        time::{TimeBaseTicks, Timestamp},
+    },
     Frame,
 };
 use ffav_sys::{
-    avfilter_graph_alloc, avfilter_graph_alloc_filter, avfilter_graph_config, avfilter_graph_dump,
-    avfilter_graph_free, avfilter_init_dict, avfilter_link, AVFilter, AVFilterContext,
-    AVFilterGraph,
+    av_buffersink_get_samples, av_buffersink_set_frame_size, av_strdup, avfilter_graph_alloc,
+    avfilter_graph_alloc_filter, avfilter_graph_config, avfilter_graph_dump, avfilter_graph_free,
+    avfilter_graph_parse_ptr, avfilter_graph_queue_command, avfilter_init_dict,
+    avfilter_inout_alloc, avfilter_inout_free, avfilter_link, avfilter_pad_get_type,
+    avfilter_process_command, err::av_err, AVFilter, AVFilterContext, AVFilterGraph, AVFilterInOut,
+    AVFILTER_CMD_FLAG_ONE,
 };
 use std::{
     ffi::{CStr, CString},
     marker::PhantomData,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
+/// The id given to the next FilterGraph which is created
+///
+/// Handles record the id of the graph which created them, because ids are
+/// never reused a handle can't be mistaken as belonging to another graph.
+static NEXT_GRAPH_ID: AtomicUsize = AtomicUsize::new(0);
+
 pub struct FilterGraph<State> {
     graph: *mut AVFilterGraph,
+    id: usize,
     filters: Vec<*mut AVFilterContext>,
     _state: PhantomData<State>,
 }
 
+impl<T> FilterGraph<T> {
+    /// Get the filter context which the provided handle refers to
+    pub(crate) fn filter_ctx<H: Handle>(&self, handle: &H) -> Result<*mut AVFilterContext> {
+        if handle.graph_id() != self.id {
+            return Err(Error::GraphDoesntOwnHandle);
+        }
+
+        self.filters
+            .get(handle.filter_id())
+            .copied()
+            .ok_or_else(|| Error::FilterNotRegisteredWithGraph {
+                name: handle.filter_config().filter_name().clone(),
+            })
+    }
+
+    /// Get every filter in the graph, including filters created by parsing a
+    /// filter description
+    fn all_filters(&self) -> &[*mut AVFilterContext] {
+        // SAFETY: The graph owns `nb_filters` valid filter contexts
+        unsafe {
+            let graph = &*self.graph;
+            if graph.filters.is_null() {
+                return &[];
+            }
+
+            std::slice::from_raw_parts(graph.filters, graph.nb_filters as usize)
+        }
+    }
+}
+
 impl FilterGraph<Unconfigured> {
     pub fn new() -> Result<FilterGraph<Unconfigured>> {
         let graph = unsafe {
@@ -38,6 +82,7 @@ impl FilterGraph<Unconfigured> {
 
         Ok(FilterGraph {
             graph,
+            id: NEXT_GRAPH_ID.fetch_add(1, Ordering::Relaxed),
             filters: Vec::new(),
             _state: PhantomData,
         })
@@ -80,7 +125,7 @@ impl FilterGraph<Unconfigured> {
         )?;
 
         Ok(FilterHandle {
-            graph_id: self.graph as usize,
+            graph_id: self.id,
             config,
             filter_id: id,
         })
@@ -103,6 +148,15 @@ impl FilterGraph<Unconfigured> {
             let cfilter_name =
                 CString::new(name).map_err(|_| Error::InvalidFilterName(name.into()))?;
 
+            // libav sends queued commands for the target "all" to every filter
+            if name == "all" {
+                return Err(Error::InvalidFilterName(name.into()));
+            }
+
+            if self.has_filter_named(&cfilter_name) {
+                return Err(Error::DuplicateFilterName(name.into()));
+            }
+
             let fctx = avfilter_graph_alloc_filter(self.graph, filter, cfilter_name.as_ptr());
 
             if fctx.is_null() {
@@ -122,6 +176,209 @@ impl FilterGraph<Unconfigured> {
         }
     }
 
+    /// Check if any filter in the graph, including filters created by parsing
+    /// a filter description, has the instance name `name`
+    fn has_filter_named(&self, name: &CStr) -> bool {
+        // SAFETY: Every filter context has a name which is either NULL or a
+        // valid C-style string
+        self.all_filters()
+            .iter()
+            .any(|&ctx| unsafe { !(*ctx).name.is_null() && CStr::from_ptr((*ctx).name) == name })
+    }
+
+    /// Add the filters described by a filter string, as passed to the ffmpeg
+    /// CLI `-vf`/`-af` options, between an `input` and `output` filter
+    ///
+    /// The first unlabeled input of the description, or the input labeled
+    /// `[in]`, is fed from `input`. The last unlabeled output, or the output
+    /// labeled `[out]`, feeds into `output`.
+    ///
+    /// The FilterGraph is consumed because libav frees every filter in the
+    /// graph when parsing fails, it is returned along with the handles on
+    /// success.
+    ///
+    /// ```ignore
+    /// let (graph, input, output) = graph.parse("scale=640:-2,format=yuv420p", source, sink)?;
+    /// ```
+    pub fn parse<I, O>(
+        mut self,
+        description: &str,
+        input: I,
+        output: O,
+    ) -> Result<(Self, InputHandle<I>, OutputHandle<O>)>
+    where
+        I: Filter + FilterInput + HasOutputPads,
+        O: Filter + FilterOutput + HasInputPads,
+    {
+        let input = self.add_input(input)?;
+        let output = self.add_output(output)?;
+
+        let links = ParseLinks::new()
+            .input("in", &input, 0)
+            .output("out", &output, 0);
+        let graph = self.parse_with_links(description, links)?;
+
+        Ok((graph, input, output))
+    }
+
+    /// Add the filters described by a filter string with several labeled
+    /// open inputs and outputs, eg. `[in0][in1]overlay[out]`
+    ///
+    /// Each `(label, filter)` pair in `inputs` and `outputs` is added to the
+    /// graph and connected to the pad with that label in the description. The
+    /// handles are returned in the same order as the pairs.
+    ///
+    /// All inputs and all outputs must be of the same filter type, to mix
+    /// types add the filters first and connect them with `parse_with_links()`.
+    /// As with `parse()` the FilterGraph is only returned on success.
+    pub fn parse_labeled<I, O>(
+        mut self,
+        description: &str,
+        inputs: Vec<(&str, I)>,
+        outputs: Vec<(&str, O)>,
+    ) -> Result<(Self, Vec<InputHandle<I>>, Vec<OutputHandle<O>>)>
+    where
+        I: Filter + FilterInput + HasOutputPads,
+        O: Filter + FilterOutput + HasInputPads,
+    {
+        let mut input_labels = Vec::with_capacity(inputs.len());
+        let mut input_handles = Vec::with_capacity(inputs.len());
+        for (label, config) in inputs {
+            input_labels.push(label);
+            input_handles.push(self.add_input(config)?);
+        }
+
+        let mut output_labels = Vec::with_capacity(outputs.len());
+        let mut output_handles = Vec::with_capacity(outputs.len());
+        for (label, config) in outputs {
+            output_labels.push(label);
+            output_handles.push(self.add_output(config)?);
+        }
+
+        let mut links = ParseLinks::new();
+        for (label, handle) in input_labels.iter().zip(&input_handles) {
+            links = links.input(label, handle, 0);
+        }
+        for (label, handle) in output_labels.iter().zip(&output_handles) {
+            links = links.output(label, handle, 0);
+        }
+
+        let graph = self.parse_with_links(description, links)?;
+
+        Ok((graph, input_handles, output_handles))
+    }
+
+    /// Add the filters described by a filter string, connecting its labeled
+    /// open inputs and outputs to filters already in this FilterGraph
+    ///
+    /// Every open pad in the description must be connected by `links`. Labels
+    /// containing a NUL byte return `Error::InvalidFilterName`. The FilterGraph
+    /// is consumed because libav frees every filter in the graph when parsing
+    /// fails, invalidating all existing handles. It is returned on success.
+    pub fn parse_with_links(self, description: &str, links: ParseLinks<'_>) -> Result<Self> {
+        let cdescription = CString::new(description)?;
+
+        // SAFETY: The AVFilterInOut lists are owned by this function and freed
+        // on all paths, `avfilter_graph_parse_ptr()` replaces them with the
+        // lists of pads which were left open.
+        unsafe {
+            // The outputs of our filters feed the open inputs of the description
+            // and vice-versa, hence the swapped names
+            let mut outputs = self.inout_list(&links.inputs)?;
+            let mut inputs = match self.inout_list(&links.outputs) {
+                Ok(list) => list,
+                Err(e) => {
+                    avfilter_inout_free(&mut outputs);
+                    return Err(e);
+                }
+            };
+
+            let err = avfilter_graph_parse_ptr(
+                self.graph,
+                cdescription.as_ptr(),
+                &mut inputs,
+                &mut outputs,
+                std::ptr::null_mut(),
+            );
+
+            let open = [inputs, outputs]
+                .iter()
+                .find(|list| !list.is_null())
+                .map(|list| inout_label(*list));
+
+            avfilter_inout_free(&mut inputs);
+            avfilter_inout_free(&mut outputs);
+
+            if err < 0 {
+                return Err(Error::from_av_err("parsing filter description", err));
+            }
+
+            if let Some(label) = open {
+                return Err(Error::UnlinkedFilterLabel(label));
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Build a list of `AVFilterInOut` for the filter pads of `links`
+    ///
+    /// # Safety
+    /// The returned list must be freed with `avfilter_inout_free()`
+    unsafe fn inout_list(&self, links: &[ParseLink<'_>]) -> Result<*mut AVFilterInOut> {
+        let labels = links
+            .iter()
+            .map(|link| {
+                CString::new(link.label.as_str())
+                    .map_err(|_| Error::InvalidFilterName(link.label.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut head: *mut AVFilterInOut = std::ptr::null_mut();
+
+        // Build the list back to front so it is in the same order as `links`
+        for (link, label) in links.iter().zip(&labels).rev() {
+            let ctx = match self.abstract_filter_ctx(&link.handle) {
+                Ok(ctx) => ctx,
+                Err(e) => {
+                    avfilter_inout_free(&mut head);
+                    return Err(e);
+                }
+            };
+
+            let inout = avfilter_inout_alloc();
+            if inout.is_null() {
+                avfilter_inout_free(&mut head);
+                return Err(Error::AllocationFailed("creating filter description links"));
+            }
+
+            (*inout).name = av_strdup(label.as_ptr());
+            (*inout).filter_ctx = ctx;
+            (*inout).pad_idx = link.pad as i32;
+            (*inout).next = head;
+            head = inout;
+
+            if (*inout).name.is_null() {
+                avfilter_inout_free(&mut head);
+                return Err(Error::AllocationFailed("creating filter description links"));
+            }
+        }
+
+        Ok(head)
+    }
+
+    fn abstract_filter_ctx(&self, handle: &AbstractHandle<'_>) -> Result<*mut AVFilterContext> {
+        if handle.graph_id != self.id {
+            return Err(Error::GraphDoesntOwnHandle);
+        }
+
+        self.filters.get(handle.filter_id).copied().ok_or_else(|| {
+            Error::FilterNotRegisteredWithGraph {
+                name: handle.filter_name.clone(),
+            }
+        })
+    }
+
     /// Connect two filters `src` and `dst` from the Ouput indexed `src_pad` and the input indexed `dest_pad`
     pub fn connect<OH, F, IH, G>(
         &mut self,
@@ -152,18 +409,11 @@ impl FilterGraph<Unconfigured> {
         dest: AbstractHandle<'h>,
         dest_pad: u32,
     ) -> Result<()> {
-        // SAFETY: We aren't dereferencing the graph and we don't care if what it
-        // points to is valid. We are merely using the pointer as an identifying
-        // mark. Because a user could drop and then recreate a FilterGraph with
-        // the same pointer htis can have a false Positive (eg filter which doesn't
-        // belong to this graph still is considered as owned because of the pointer
-        // match) but it isn't feasible to have the handle tied to the lifetime
-        // because it complicates the borrow and Send/Sync details.
-        if src.graph_id != self.graph as usize {
+        if src.graph_id != self.id {
             return Err(Error::GraphDoesntOwnHandle);
         }
 
-        if dest.graph_id != self.graph as usize {
+        if dest.graph_id != self.id {
             return Err(Error::GraphDoesntOwnHandle);
         }
 
@@ -171,10 +421,8 @@ impl FilterGraph<Unconfigured> {
         let filter_src = self
             .filters
             .get(src.filter_id)
-            // This should never happen because this handle should belong
-            // to this graph, but we will check here just in case because
-            // its possible someone deallocated and reallocated the FilterGraph
-            // and got the same pointer so we passed the previous checks
+            // This should never happen because graph ids are unique and only
+            // this graph creates handles with its id, but check just in case
             .ok_or(Error::FilterNotRegisteredWithGraph {
                 name: src.filter_name.clone(),
             })?;
@@ -194,22 +442,18 @@ impl FilterGraph<Unconfigured> {
         unsafe {
             // NOTE: Here we check that the pads which the user requested
             //     a) Exist
-            //     b) Are of compatible types
+            //     b) Aren't already linked
+            //     c) Are of compatible types
             // Technically `avfilter_link()` will do these checks too, but it
             // will return just `EINVAL` which isn't very helpful.
             // Because this is a configuration step I justify that it is okay to
             // double check the types match and that the pad count's match so
             // that we can return a useful error message.
 
-            // SAFETY: It is safe to index the array `outputs` by `src_pad`
-            // because we know that there are at least as many outputs per
-            // `nb_outputs`, if this isn't true ffmpeg has violated an invariant
-            // that we cannot check
-            let output_slice = std::slice::from_raw_parts(
-                (**filter_src).outputs,
-                (**filter_src).nb_outputs as usize,
-            );
-            if output_slice.len() <= src_pad as usize || !output_slice[src_pad as usize].is_null() {
+            // NOTE: The pad arrays of a filter context aren't terminated like the
+            // arrays on the `AVFilter`, so `nb_outputs`/`nb_inputs` give the
+            // number of pads rather than `avfilter_pad_count()`.
+            if src_pad >= (**filter_src).nb_outputs {
                 return Err(Error::OutputPadDoesntExist {
                     name: src.filter_name.clone(),
                     filter_type: src.filter_type_name,
@@ -217,15 +461,7 @@ impl FilterGraph<Unconfigured> {
                 });
             }
 
-            // SAFETY: It is safe to index the array `inputs` by `dest_pad`
-            // because we know that there are at least as many outputs per
-            // `nb_inputs`, if this isn't true ffmpeg has violated an invariant
-            // that we cannot check
-            let input_slice = std::slice::from_raw_parts(
-                (**filter_dest).inputs,
-                (**filter_dest).nb_inputs as usize,
-            );
-            if input_slice.len() <= dest_pad as usize || !input_slice[dest_pad as usize].is_null() {
+            if dest_pad >= (**filter_dest).nb_inputs {
                 return Err(Error::InputPadDoesntExist {
                     name: dest.filter_name.clone(),
                     filter_type: dest.filter_type_name,
@@ -233,19 +469,31 @@ impl FilterGraph<Unconfigured> {
                 });
             }
 
-            // SAFETY: We know that both input pads exist and are not null from
-            // the checks above, these should be safe to get
+            // SAFETY: Both pads exist from the checks above, so it is safe to
+            // index the link arrays which have an entry per pad.
+            if !(*(**filter_src).outputs.add(src_pad as usize)).is_null() {
+                return Err(Error::OutputPadAlreadyLinked {
+                    name: src.filter_name.clone(),
+                    pad_number: src_pad,
+                });
+            }
 
-            // FIXME: This needs to be done on pads, not links
-            // let output_pad = *(**filter_src).outputs.add(src_pad as usize);
-            // let input_pad = *(**filter_dest).inputs.add(dest_pad as usize);
+            if !(*(**filter_dest).inputs.add(dest_pad as usize)).is_null() {
+                return Err(Error::InputPadAlreadyLinked {
+                    name: dest.filter_name.clone(),
+                    pad_number: dest_pad,
+                });
+            }
 
-            // if (*output_pad).type_ != (*input_pad).type_ {
-            //     return Err(Error::PadTypeMismatch {
-            //         src_type: (*output_pad).type_,
-            //         dest_type: (*input_pad).type_,
-            //     });
-            // }
+            let src_type = avfilter_pad_get_type((**filter_src).output_pads, src_pad as i32);
+            let dest_type = avfilter_pad_get_type((**filter_dest).input_pads, dest_pad as i32);
+
+            if src_type != dest_type {
+                return Err(Error::PadTypeMismatch {
+                    src_type,
+                    dest_type,
+                });
+            }
 
             let err = avfilter_link(*filter_src, src_pad, *filter_dest, dest_pad);
             if err < 0 {
@@ -257,6 +505,10 @@ impl FilterGraph<Unconfigured> {
         Ok(())
     }
 
+    /// Check the connections and negotiate the formats between all filters
+    ///
+    /// The configured FilterGraph keeps its id, so handles created while
+    /// building the graph remain valid.
     pub fn configure(self) -> Result<FilterGraph<Configured>> {
         // SAFETY: We know that `self.graph` is non-NULL because we have not been
         // dropped.
@@ -275,7 +527,202 @@ impl FilterGraph<Unconfigured> {
     }
 }
 
+fn command_error<H: Handle>(handle: &H, cmd: &str, err: i32) -> Error {
+    if err == av_err(ffav_sys::err::ENOSYS) {
+        Error::FilterCommandNotSupported {
+            name: handle.filter_config().filter_name().clone(),
+            command: cmd.into(),
+        }
+    } else {
+        Error::from_av_err("sending filter command", err)
+    }
+}
+
+/// Get the label of an open pad left after parsing a filter description
+///
+/// # Safety
+/// `inout` must be a valid non-NULL `AVFilterInOut`
+unsafe fn inout_label(inout: *mut AVFilterInOut) -> String {
+    if (*inout).name.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr((*inout).name).to_string_lossy().into_owned()
+    }
+}
+
+/// Connections between filters in a FilterGraph and the labeled open pads of
+/// a filter description passed to `FilterGraph::parse_with_links()`
+#[derive(Default)]
+pub struct ParseLinks<'h> {
+    inputs: Vec<ParseLink<'h>>,
+    outputs: Vec<ParseLink<'h>>,
+}
+
+struct ParseLink<'h> {
+    label: String,
+    handle: AbstractHandle<'h>,
+    pad: u32,
+}
+
+impl<'h> ParseLinks<'h> {
+    /// Create an empty set of links
+    pub fn new() -> ParseLinks<'h> {
+        Self::default()
+    }
+
+    /// Feed the output pad `pad` of the filter `src` into the input labeled
+    /// `label` in the description
+    pub fn input<H, F>(mut self, label: &str, src: &'h H, pad: u32) -> Self
+    where
+        H: Handle<FilterType = F>,
+        F: Filter + HasOutputPads,
+    {
+        self.inputs.push(ParseLink {
+            label: label.to_string(),
+            handle: src.abstract_handle(),
+            pad,
+        });
+        self
+    }
+
+    /// Feed the output labeled `label` in the description into the input pad
+    /// `pad` of the filter `dest`
+    pub fn output<H, F>(mut self, label: &str, dest: &'h H, pad: u32) -> Self
+    where
+        H: Handle<FilterType = F>,
+        F: Filter + HasInputPads,
+    {
+        self.outputs.push(ParseLink {
+            label: label.to_string(),
+            handle: dest.abstract_handle(),
+            pad,
+        });
+        self
+    }
+}
+
 impl FilterGraph<Configured> {
+    /// Change the parameters of an updatable filter immediately
+    ///
+    /// Returns the response of the filter, which is empty for most filters.
+    pub fn update<H, F>(&mut self, handle: &H, command: &F::Command) -> Result<String>
+    where
+        H: Handle<FilterType = F>,
+        F: UpdatableFilter,
+    {
+        let (cmd, arg) = F::command_args(command);
+
+        self.send_command(handle, &cmd, &arg)
+    }
+
+    /// Schedule a change to the parameters of an updatable filter
+    ///
+    /// The change is applied once the filter processes a frame at or after
+    /// the timestamp `at`.
+    pub fn queue_update<H, F>(
+        &mut self,
+        handle: &H,
+        command: &F::Command,
+        at: Timestamp,
+    ) -> Result<()>
+    where
+        H: Handle<FilterType = F>,
+        F: UpdatableFilter,
+    {
+        let (cmd, arg) = F::command_args(command);
+
+        self.queue_command(handle, &cmd, &arg, at)
+    }
+
+    /// Send a command to a filter immediately
+    ///
+    /// The commands supported by each filter are listed in the ffmpeg filter
+    /// documentation. Returns the response of the filter, which is empty for
+    /// most filters.
+    pub fn send_command<H: Handle>(&mut self, handle: &H, cmd: &str, arg: &str) -> Result<String> {
+        let filter = self.filter_ctx(handle)?;
+
+        let ccmd = CString::new(cmd)?;
+        let carg = CString::new(arg)?;
+
+        let mut response = [0 as std::os::raw::c_char; 256];
+
+        // SAFETY: We know that the filter is not NULL and belongs to this graph.
+        // The command is sent to the context directly because libav would
+        // look the target up by name, which can also match other filters of
+        // the same type.
+        unsafe {
+            let err = avfilter_process_command(
+                filter,
+                ccmd.as_ptr(),
+                carg.as_ptr(),
+                response.as_mut_ptr(),
+                response.len() as i32,
+                0,
+            );
+
+            if err < 0 {
+                return Err(command_error(handle, cmd, err));
+            }
+
+            Ok(CStr::from_ptr(response.as_ptr())
+                .to_string_lossy()
+                .into_owned())
+        }
+    }
+
+    /// Schedule a command to be sent to a filter
+    ///
+    /// The command is sent once the filter processes a frame at or after the
+    /// timestamp `at`.
+    ///
+    /// libav addresses queued commands by name and matches both the name and
+    /// the filter type of every filter in the graph. If the name of the filter
+    /// is also the type of another filter in the graph (eg. a filter named
+    /// "volume" in a graph containing a `Volume` filter) this returns
+    /// `Error::AmbiguousCommandTarget` rather than reaching both.
+    pub fn queue_command<H: Handle>(
+        &mut self,
+        handle: &H,
+        cmd: &str,
+        arg: &str,
+        at: Timestamp,
+    ) -> Result<()> {
+        let filter = self.filter_ctx(handle)?;
+
+        let name = handle.filter_config().filter_name();
+        let target = CString::new(name.as_str())?;
+
+        // SAFETY: Every filter context has a valid filter type with a valid
+        // C-style name
+        let ambiguous = self.all_filters().iter().any(|&ctx| unsafe {
+            ctx != filter && CStr::from_ptr((*(*ctx).filter).name) == target.as_c_str()
+        });
+        if ambiguous {
+            return Err(Error::AmbiguousCommandTarget(name.clone()));
+        }
+
+        let ccmd = CString::new(cmd)?;
+        let carg = CString::new(arg)?;
+
+        unsafe {
+            let err = avfilter_graph_queue_command(
+                self.graph,
+                target.as_ptr(),
+                ccmd.as_ptr(),
+                carg.as_ptr(),
+                AVFILTER_CMD_FLAG_ONE,
+                at.as_secs_f64(),
+            );
+
+            if err < 0 {
+                return Err(command_error(handle, cmd, err));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Submit input to the FilterGraph through the specified input
     pub fn submit_input<F: Filter + FilterInput>(
         &mut self,
@@ -285,17 +732,15 @@ impl FilterGraph<Configured> {
     where
         InputHandle<F>: Handle,
     {
-        if input.graph_id() != self.graph as usize {
+        if input.graph_id() != self.id {
             return Err(Error::GraphDoesntOwnHandle);
         }
 
         let filter = self
             .filters
             .get(input.filter_id())
-            // This should never happen because this handle should belong
-            // to this graph, but we will check here just in case because
-            // its possible someone deallocated and reallocated the FilterGraph
-            // and got the same pointer so we passed the previous checks
+            // This should never happen because graph ids are unique and only
+            // this graph creates handles with its id, but check just in case
             .ok_or(Error::FilterNotRegisteredWithGraph {
                 name: input.filter_config().filter_name().clone(),
             })?;
@@ -315,6 +760,23 @@ impl FilterGraph<Configured> {
         }
     }
 
+    /// Signal that no more frames will be submitted through the specified input
+    ///
+    /// `pts` is the time the input ended in the TimeBase of the input, or None
+    /// if it isn't known. Filters which buffer frames, eg. `atrim` or `fps`,
+    /// will then output their remaining frames. Once every input has been
+    /// closed and all frames have been retrieved the outputs return `Error::EoF`.
+    pub fn close_input<F: Filter + FilterInput>(
+        &mut self,
+        input: &InputHandle<F>,
+        pts: Option<TimeBaseTicks>,
+    ) -> Result<()> {
+        let filter = self.filter_ctx(input)?;
+
+        // SAFETY: We know that the filter is not NULL and is of the input type `F`
+        unsafe { F::close(filter, pts) }
+    }
+
     /// Get output from the FilterGraph from the specified output and place it in the provided Frame
     ///
     /// Existing data in the frame will be unreferenced
@@ -323,17 +785,15 @@ impl FilterGraph<Configured> {
         output: &OutputHandle<F>,
         frame: &mut Frame<F::StreamType>,
     ) -> Result<()> {
-        if output.graph_id() != self.graph as usize {
+        if output.graph_id() != self.id {
             return Err(Error::GraphDoesntOwnHandle);
         }
 
         let filter = self
             .filters
             .get(output.filter_id())
-            // This should never happen because this handle should belong
-            // to this graph, but we will check here just in case because
-            // its possible someone deallocated and reallocated the FilterGraph
-            // and got the same pointer so we passed the previous checks
+            // This should never happen because graph ids are unique and only
+            // this graph creates handles with its id, but check just in case
             .ok_or(Error::FilterNotRegisteredWithGraph {
                 name: output.filter_config().filter_name().clone(),
             })?;
@@ -358,17 +818,15 @@ impl FilterGraph<Configured> {
         &mut self,
         output: &OutputHandle<F>,
     ) -> Result<Frame<F::StreamType>> {
-        if output.graph_id() != self.graph as usize {
+        if output.graph_id() != self.id {
             return Err(Error::GraphDoesntOwnHandle);
         }
 
         let filter = self
             .filters
             .get(output.filter_id())
-            // This should never happen because this handle should belong
-            // to this graph, but we will check here just in case because
-            // its possible someone deallocated and reallocated the FilterGraph
-            // and got the same pointer so we passed the previous checks
+            // This should never happen because graph ids are unique and only
+            // this graph creates handles with its id, but check just in case
             .ok_or(Error::FilterNotRegisteredWithGraph {
                 name: output.filter_config().filter_name().clone(),
             })?;
@@ -385,6 +843,99 @@ impl FilterGraph<Configured> {
             })
         }
     }
+
+    /// Get the properties of the frames which will be produced by an output
+    ///
+    /// These are negotiated between the filters when the FilterGraph is
+    /// configured and don't change afterwards.
+    pub fn output_config<F: Filter + FilterOutput>(
+        &self,
+        output: &OutputHandle<F>,
+    ) -> Result<OutputConfig<F::StreamType>> {
+        let filter = self.filter_ctx(output)?;
+
+        // SAFETY: We know that the filter is not NULL and is a buffer sink
+        // producing frames of `F::StreamType`, and the graph is configured
+        unsafe { OutputConfig::from_buffersink(filter) }
+    }
+
+    /// Make an audio output return frames of exactly `num_samples` samples
+    ///
+    /// Samples are buffered in the output until a full frame is available,
+    /// only the last frame at the end of the stream may be shorter.
+    pub fn set_output_frame_size<F>(
+        &mut self,
+        output: &OutputHandle<F>,
+        num_samples: u32,
+    ) -> Result<()>
+    where
+        F: Filter + FilterOutput<StreamType = Audio>,
+    {
+        if num_samples == 0 {
+            return Err(Error::InvalidArguments("setting output frame size"));
+        }
+
+        let filter = self.filter_ctx(output)?;
+
+        // SAFETY: We know that the filter is not NULL and is an audio buffer
+        // sink which is linked to its input because the graph is configured
+        unsafe { av_buffersink_set_frame_size(filter, num_samples) };
+
+        Ok(())
+    }
+
+    /// Get exactly `num_samples` samples from an audio output and place them
+    /// in the provided Frame
+    ///
+    /// Returns `Error::SubmitMoreInput` until enough samples are buffered in
+    /// the output, only the last frame at the end of the stream may be
+    /// shorter. Existing data in the frame will be unreferenced.
+    pub fn get_output_samples_into<F>(
+        &mut self,
+        output: &OutputHandle<F>,
+        frame: &mut Frame<Audio>,
+        num_samples: u32,
+    ) -> Result<()>
+    where
+        F: Filter + FilterOutput<StreamType = Audio>,
+    {
+        if num_samples == 0 {
+            return Err(Error::InvalidArguments("getting samples from FilterGraph"));
+        }
+
+        let filter = self.filter_ctx(output)?;
+
+        frame.unref();
+
+        // SAFETY: We know that the filter is not NULL and is an audio buffer sink
+        let err = unsafe { av_buffersink_get_samples(filter, frame.as_raw(), num_samples as i32) };
+
+        if err == av_err(ffav_sys::err::EAGAIN) {
+            return Err(Error::SubmitMoreInput);
+        } else if err < 0 {
+            return Err(Error::from_av_err("getting samples from FilterGraph", err));
+        }
+
+        Ok(())
+    }
+
+    /// Get exactly `num_samples` samples from an audio output
+    ///
+    /// See `get_output_samples_into()`
+    pub fn get_output_samples<F>(
+        &mut self,
+        output: &OutputHandle<F>,
+        num_samples: u32,
+    ) -> Result<Frame<Audio>>
+    where
+        F: Filter + FilterOutput<StreamType = Audio>,
+    {
+        let mut frame = Frame::new();
+
+        self.get_output_samples_into(output, &mut frame, num_samples)?;
+
+        Ok(frame)
+    }
 }
 
 impl<T> std::ops::Drop for FilterGraph<T> {
@@ -1,16 +1,19 @@
 pub mod audio;
 mod graph;
+mod output;
 pub mod video;
 
 use crate::{
     config::Dictionary,
     error::{Error, Result},
+    util::time::TimeBaseTicks,
     Frame,
 };
-use ffav_sys::{avfilter_get_by_name, AVFilterContext};
+use ffav_sys::{av_buffersrc_close, avfilter_get_by_name, AVFilterContext, AV_BUFFERSRC_FLAG_PUSH};
 use std::ffi::CString;
 
-pub use graph::FilterGraph;
+pub use graph::{FilterGraph, ParseLinks};
+pub use output::OutputConfig;
 
 pub struct InputHandle<F>(pub(crate) FilterHandle<F>);
 pub struct OutputHandle<F>(pub(crate) FilterHandle<F>);
@@ -66,6 +69,8 @@ pub trait Filter {
     fn filter_type_name() -> CString;
 
     /// Get the name of the filter this must be unique for every filter in the FilterGraph
+    ///
+    /// The name "all" is reserved by libav to address every filter.
     fn filter_name(&self) -> &String;
 
     /// Get the dictionary of options to initially configure the filter
@@ -82,13 +87,40 @@ pub trait FilterInput {
     /// Submit a fram to the appropriate input type filter
     ///
     /// # Safety
-    /// Thsi function is used internally by the FilterGraph and shouldn't be
+    /// This function is used internally by the FilterGraph and shouldn't be
     /// called directly by a user. The appropriate `*mut FilterContext` will be
     /// provided by the FilterGraph.
     unsafe fn submit_frame(
         filter: *mut AVFilterContext,
         frame: &mut Frame<Self::StreamType>,
     ) -> Result<()>;
+
+    /// Mark the end of the input, no more frames can be submitted afterwards
+    ///
+    /// `pts` is the time the input ended in the TimeBase of the input, or
+    /// None if it isn't known. With None the end is marked without a
+    /// timestamp, so filters which use the end time, eg. `fps` or `atrim`,
+    /// can't account for the duration of the last frame.
+    ///
+    /// # Safety
+    /// This function is used internally by the FilterGraph and shouldn't be
+    /// called directly by a user. The appropriate `*mut FilterContext` will be
+    /// provided by the FilterGraph.
+    unsafe fn close(filter: *mut AVFilterContext, pts: Option<TimeBaseTicks>) -> Result<()> {
+        // Push the buffered frames through the graph straight away so they are
+        // ready in the outputs
+        let err = av_buffersrc_close(
+            filter,
+            TimeBaseTicks::option_as_av_timestamp(pts),
+            AV_BUFFERSRC_FLAG_PUSH as _,
+        );
+
+        if err < 0 {
+            return Err(Error::from_av_err("closing FilterGraph input", err));
+        }
+
+        Ok(())
+    }
 }
 /// Indicates a type which can be used as output from the FilterGraph
 pub trait FilterOutput {
@@ -97,7 +129,7 @@ pub trait FilterOutput {
     /// Get a frame from the FilterGraph output and place it into an allocated frame
     ///
     /// # Safety
-    /// Thsi function is used internally by the FilterGraph and shouldn't be
+    /// This function is used internally by the FilterGraph and shouldn't be
     /// called directly by a user. The appropriate `*mut FilterContext` will be
     /// provided by the FilterGraph.
     unsafe fn get_frame_into(
@@ -108,7 +140,7 @@ pub trait FilterOutput {
     /// Get a frame from the FilterGraph output and return a newly allocated frame
     ///
     /// # Safety
-    /// Thsi function is used internally by the FilterGraph and shouldn't be
+    /// This function is used internally by the FilterGraph and shouldn't be
     /// called directly by a user. The appropriate `*mut FilterContext` will be
     /// provided by the FilterGraph.
     unsafe fn get_frame(filter: *mut AVFilterContext) -> Result<Frame<Self::StreamType>> {
@@ -185,5 +217,37 @@ impl<F: Filter + FilterOutput> Handle for OutputHandle<F> {
     }
 }
 
-// TODO: Support updatable filter configurations
-// pub trait UpdatableFilter: FilterParameters {...}
+/// A filter whose parameters can be changed while a configured FilterGraph
+/// is running
+///
+/// Commands are sent with `FilterGraph::update()` or scheduled with
+/// `FilterGraph::queue_update()`. The configuration held by the filter's
+/// handle is not changed by commands.
+pub trait UpdatableFilter: Filter {
+    /// A change to the filter's parameters
+    type Command;
+
+    /// Get the command name and argument understood by the libav filter
+    fn command_args(command: &Self::Command) -> (String, String);
+}
+
+/// Encode a list of values as the hex string used to set binary options, such
+/// as the format lists of the buffer sinks
+///
+/// Each value is given as its native endian bytes, as libav copies the decoded
+/// bytes directly into an array of the option's type.
+pub(crate) fn binary_option_arg<I, B>(values: I) -> String
+where
+    I: IntoIterator<Item = B>,
+    B: AsRef<[u8]>,
+{
+    let mut arg = String::new();
+
+    for value in values {
+        for byte in value.as_ref() {
+            arg.push_str(&format!("{:02x}", byte));
+        }
+    }
+
+    arg
+}
@@ -0,0 +1,150 @@
+use std::marker::PhantomData;
+
+use ffav_sys::{
+    av_buffersink_get_channel_layout, av_buffersink_get_format, av_buffersink_get_frame_rate,
+    av_buffersink_get_h, av_buffersink_get_sample_aspect_ratio, av_buffersink_get_sample_rate,
+    av_buffersink_get_time_base, av_buffersink_get_w, AVFilterContext, AVPixelFormat,
+    AVSampleFormat,
+};
+
+use crate::{
+    error::{Error, Result},
+    util::{
+        channels::ChannelLayout,
+        color::PixelFormat,
+        marker::{Audio, Video},
+        sampling::SampleFormat,
+        time::{FrameRate, SampleRate, TimeBase},
+    },
+};
+
+/// Describes the frames which will be produced by an output of a configured
+/// FilterGraph
+///
+/// The properties are negotiated between the filters when the FilterGraph is
+/// configured. They can be passed to an encoder with
+/// `EncoderBuilder::filter_output()`.
+#[derive(Debug, Clone)]
+pub struct OutputConfig<AV> {
+    time_base: TimeBase,
+    // Either a pixel format or sample format depending on the media type
+    format: i32,
+
+    // Video specific parameters
+    frame_rate: Option<FrameRate>,
+    width: u32,
+    height: u32,
+    sample_aspect_ratio: Option<(u32, u32)>,
+
+    // Audio specific parameters
+    sample_rate: SampleRate,
+    channel_layout: ChannelLayout,
+
+    _type: PhantomData<AV>,
+}
+
+impl<AV> OutputConfig<AV> {
+    /// Read the negotiated properties of a buffer sink
+    ///
+    /// Fails if the sink has no valid time base, which libav should always
+    /// set when the graph is configured
+    ///
+    /// # Safety
+    /// `sink` must be a `buffersink` or `abuffersink` filter in a configured
+    /// graph producing frames of type `AV`
+    pub(crate) unsafe fn from_buffersink(sink: *mut AVFilterContext) -> Result<OutputConfig<AV>> {
+        let sample_aspect_ratio = av_buffersink_get_sample_aspect_ratio(sink);
+
+        Ok(OutputConfig {
+            time_base: TimeBase::from_av_rational(&av_buffersink_get_time_base(sink))
+                .ok_or(Error::InvalidArguments("reading the buffer sink time base"))?,
+            format: av_buffersink_get_format(sink),
+            frame_rate: FrameRate::from_av_rational(&av_buffersink_get_frame_rate(sink)),
+            width: av_buffersink_get_w(sink).max(0) as u32,
+            height: av_buffersink_get_h(sink).max(0) as u32,
+            // libav uses `0/1` for an unknown aspect ratio
+            sample_aspect_ratio: if sample_aspect_ratio.num > 0 && sample_aspect_ratio.den > 0 {
+                Some((
+                    sample_aspect_ratio.num as u32,
+                    sample_aspect_ratio.den as u32,
+                ))
+            } else {
+                None
+            },
+            sample_rate: SampleRate::new(av_buffersink_get_sample_rate(sink).max(0) as u32),
+            channel_layout: ChannelLayout::from_bits_truncate(av_buffersink_get_channel_layout(
+                sink,
+            )),
+            _type: PhantomData,
+        })
+    }
+
+    /// Get the TimeBase of the timestamps of the produced frames
+    pub fn time_base(&self) -> TimeBase {
+        self.time_base
+    }
+}
+
+impl OutputConfig<Video> {
+    /// The width of the produced frames
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the produced frames
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The pixel format of the produced frames
+    pub fn pixel_format(&self) -> PixelFormat {
+        // Safety: The sink reports the format as an i32 which corresponds to
+        // `AVPixelFormat` for video outputs, so it is safe to transmute. Then
+        // we can convert to the high level `PixelFormat` type.
+        let av_fmt: AVPixelFormat = unsafe { std::mem::transmute(self.format) };
+
+        PixelFormat::from(av_fmt)
+    }
+
+    /// Get the frame-rate of the produced frames
+    ///
+    /// Will return None if the value is not known, eg. for variable frame-rate
+    /// video
+    pub fn frame_rate(&self) -> Option<FrameRate> {
+        self.frame_rate
+    }
+
+    /// Get the aspect ratio of a single pixel as `(numerator, denominator)`
+    ///
+    /// Will return None if the value is not known
+    pub fn sample_aspect_ratio(&self) -> Option<(u32, u32)> {
+        self.sample_aspect_ratio
+    }
+}
+
+impl OutputConfig<Audio> {
+    /// The format the samples of the produced frames are stored in
+    pub fn sample_format(&self) -> SampleFormat {
+        // Safety: The sink reports the format as an i32 which corresponds to
+        // `AVSampleFormat` for audio outputs, so it is safe to transmute. Then
+        // we can convert to the high level `SampleFormat` type.
+        let av_fmt: AVSampleFormat = unsafe { std::mem::transmute(self.format) };
+
+        SampleFormat::from(av_fmt)
+    }
+
+    /// The sample rate of the produced frames
+    pub fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    /// The channel layout of the produced frames
+    pub fn channel_layout(&self) -> ChannelLayout {
+        self.channel_layout
+    }
+
+    /// How many channels the produced frames contain
+    pub fn num_channels(&self) -> u32 {
+        self.channel_layout.bits().count_ones()
+    }
+}
@@ -47,6 +47,11 @@ impl Filter for VBufferSource {
             format!("{}", self.incoming_stream.pixel_format()),
         );
 
+        // Lets the frame-rate be read from the FilterGraph outputs
+        if let Some(frame_rate) = self.incoming_stream.frame_rate() {
+            dict.add("frame_rate", format!("{}", frame_rate));
+        }
+
         dict
     }
 }
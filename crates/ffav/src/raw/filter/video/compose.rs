@@ -0,0 +1,226 @@
+use std::ffi::CString;
+
+use crate::{
+    config::Dictionary,
+    raw::filter::{Filter, HasInputPads, HasOutputPads, UpdatableFilter},
+};
+
+use super::Color;
+
+/// What `VOverlay` does when the overlaid input ends before the main input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverlayEofAction {
+    /// Keep overlaying the last frame
+    Repeat,
+    /// End the output
+    EndAll,
+    /// Pass the main input through unchanged
+    Pass,
+}
+
+/// A change to the position of the overlaid video for `VOverlay`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverlayCommand {
+    X(i32),
+    Y(i32),
+}
+
+/// Draw one video on top of another
+///
+/// Input pad 0 is the main video and input pad 1 is drawn on top of it.
+pub struct VOverlay {
+    name: String,
+    x: i32,
+    y: i32,
+    eof_action: OverlayEofAction,
+    shortest: bool,
+}
+
+impl VOverlay {
+    /// Overlay the video with its top left corner at `x`, `y` in the main video
+    pub fn new(name: impl Into<String>, x: i32, y: i32) -> Self {
+        VOverlay {
+            name: name.into(),
+            x,
+            y,
+            eof_action: OverlayEofAction::Repeat,
+            shortest: false,
+        }
+    }
+
+    pub fn eof_action(mut self, action: OverlayEofAction) -> Self {
+        self.eof_action = action;
+        self
+    }
+
+    /// End the output when the shortest input ends
+    pub fn shortest(mut self, shortest: bool) -> Self {
+        self.shortest = shortest;
+        self
+    }
+}
+
+impl HasInputPads for VOverlay {}
+impl HasOutputPads for VOverlay {}
+
+impl Filter for VOverlay {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("overlay").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("x", format!("{}", self.x));
+        dict.add("y", format!("{}", self.y));
+
+        let eof_action = match self.eof_action {
+            OverlayEofAction::Repeat => "repeat",
+            OverlayEofAction::EndAll => "endall",
+            OverlayEofAction::Pass => "pass",
+        };
+        dict.add("eof_action", eof_action);
+        dict.add("shortest", format!("{}", if self.shortest { 1 } else { 0 }));
+
+        dict
+    }
+}
+
+impl UpdatableFilter for VOverlay {
+    type Command = OverlayCommand;
+
+    fn command_args(command: &OverlayCommand) -> (String, String) {
+        match command {
+            OverlayCommand::X(x) => ("x".into(), format!("{}", x)),
+            OverlayCommand::Y(y) => ("y".into(), format!("{}", y)),
+        }
+    }
+}
+
+/// The thickness of the edges drawn by `VDrawBox`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BoxThickness {
+    Pixels(u32),
+    /// Fill the whole box
+    Fill,
+}
+
+/// Draw a rectangle onto the video
+pub struct VDrawBox {
+    name: String,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    color: Color,
+    thickness: BoxThickness,
+}
+
+impl VDrawBox {
+    /// Draw the outline of a box with its top left corner at `x`, `y`
+    pub fn new(
+        name: impl Into<String>,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        color: Color,
+    ) -> Self {
+        VDrawBox {
+            name: name.into(),
+            x,
+            y,
+            width,
+            height,
+            color,
+            thickness: BoxThickness::Pixels(3),
+        }
+    }
+
+    /// Set the thickness of the edges, 3 pixels by default
+    pub fn thickness(mut self, thickness: BoxThickness) -> Self {
+        self.thickness = thickness;
+        self
+    }
+}
+
+impl HasInputPads for VDrawBox {}
+impl HasOutputPads for VDrawBox {}
+
+impl Filter for VDrawBox {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("drawbox").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("x", format!("{}", self.x));
+        dict.add("y", format!("{}", self.y));
+        dict.add("w", format!("{}", self.width));
+        dict.add("h", format!("{}", self.height));
+        dict.add("color", format!("{}", self.color));
+
+        match self.thickness {
+            BoxThickness::Pixels(pixels) => dict.add("t", format!("{}", pixels)),
+            BoxThickness::Fill => dict.add("t", "fill"),
+        }
+
+        dict
+    }
+}
+
+/// Duplicate the video to several output pads
+pub struct VSplit {
+    name: String,
+    outputs: u32,
+}
+
+impl VSplit {
+    /// Split the video into `outputs` identical streams, output pads are
+    /// numbered from 0
+    pub fn new(name: impl Into<String>, outputs: u32) -> Self {
+        VSplit {
+            name: name.into(),
+            outputs,
+        }
+    }
+}
+
+impl HasInputPads for VSplit {}
+impl HasOutputPads for VSplit {}
+
+impl Filter for VSplit {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("split").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("outputs", format!("{}", self.outputs));
+
+        dict
+    }
+}
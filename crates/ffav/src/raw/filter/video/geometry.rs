@@ -0,0 +1,438 @@
+use std::ffi::CString;
+
+use crate::{
+    config::Dictionary,
+    raw::{
+        filter::{Filter, HasInputPads, HasOutputPads, UpdatableFilter},
+        scale::ScalingAlgorithm,
+    },
+    util::color::ColorRange,
+};
+
+use super::Color;
+
+/// One side of the output size of the `VScale` filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScaleDimension {
+    /// An exact number of pixels
+    Pixels(u32),
+    /// Calculated from the other dimension to keep the aspect ratio of the
+    /// input, rounded to a multiple of 2 as required by most YUV formats
+    KeepAspect,
+}
+
+impl ScaleDimension {
+    fn as_arg(self) -> String {
+        match self {
+            ScaleDimension::Pixels(pixels) => format!("{}", pixels),
+            ScaleDimension::KeepAspect => "-2".into(),
+        }
+    }
+}
+
+/// Resize the video
+pub struct VScale {
+    name: String,
+    width: ScaleDimension,
+    height: ScaleDimension,
+    algorithm: ScalingAlgorithm,
+    color_range: Option<ColorRange>,
+}
+
+impl VScale {
+    pub fn new(name: impl Into<String>, width: u32, height: u32) -> Self {
+        Self::new_with_dimensions(
+            name,
+            ScaleDimension::Pixels(width),
+            ScaleDimension::Pixels(height),
+        )
+    }
+
+    /// Only one of the dimensions should be `ScaleDimension::KeepAspect`
+    pub fn new_with_dimensions(
+        name: impl Into<String>,
+        width: ScaleDimension,
+        height: ScaleDimension,
+    ) -> Self {
+        VScale {
+            name: name.into(),
+            width,
+            height,
+            algorithm: ScalingAlgorithm::default(),
+            color_range: None,
+        }
+    }
+
+    /// Set the algorithm used to resize the video
+    pub fn algorithm(mut self, algorithm: ScalingAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Set the color range of the output video, by default the input range is kept
+    pub fn color_range(mut self, range: ColorRange) -> Self {
+        self.color_range = Some(range);
+        self
+    }
+}
+
+impl HasInputPads for VScale {}
+impl HasOutputPads for VScale {}
+
+impl Filter for VScale {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("scale").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("w", self.width.as_arg());
+        dict.add("h", self.height.as_arg());
+        dict.add("flags", self.algorithm.option_name());
+
+        match self.color_range {
+            Some(ColorRange::Limited) => dict.add("out_range", "tv"),
+            Some(ColorRange::Full) => dict.add("out_range", "pc"),
+            Some(ColorRange::Unspecified) | None => {}
+        }
+
+        dict
+    }
+}
+
+/// Cut a rectangular region out of the video
+pub struct VCrop {
+    name: String,
+    width: u32,
+    height: u32,
+    position: Option<(u32, u32)>,
+}
+
+impl VCrop {
+    /// Crop a region from the center of the video
+    pub fn new(name: impl Into<String>, width: u32, height: u32) -> Self {
+        VCrop {
+            name: name.into(),
+            width,
+            height,
+            position: None,
+        }
+    }
+
+    /// Crop a region with its top left corner at `x`, `y`
+    pub fn new_at(name: impl Into<String>, width: u32, height: u32, x: u32, y: u32) -> Self {
+        VCrop {
+            name: name.into(),
+            width,
+            height,
+            position: Some((x, y)),
+        }
+    }
+}
+
+impl HasInputPads for VCrop {}
+impl HasOutputPads for VCrop {}
+
+impl Filter for VCrop {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("crop").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("w", format!("{}", self.width));
+        dict.add("h", format!("{}", self.height));
+
+        if let Some((x, y)) = self.position {
+            dict.add("x", format!("{}", x));
+            dict.add("y", format!("{}", y));
+        }
+
+        dict
+    }
+}
+
+/// Place the video on a larger canvas
+pub struct VPad {
+    name: String,
+    width: u32,
+    height: u32,
+    position: Option<(u32, u32)>,
+    color: Color,
+}
+
+impl VPad {
+    /// Pad the video to `width` x `height` keeping the video in the center
+    pub fn new(name: impl Into<String>, width: u32, height: u32) -> Self {
+        VPad {
+            name: name.into(),
+            width,
+            height,
+            position: None,
+            color: Color::BLACK,
+        }
+    }
+
+    /// Pad the video to `width` x `height` with the top left corner of the
+    /// video at `x`, `y`
+    pub fn new_at(name: impl Into<String>, width: u32, height: u32, x: u32, y: u32) -> Self {
+        VPad {
+            position: Some((x, y)),
+            ..Self::new(name, width, height)
+        }
+    }
+
+    /// Set the color of the padded area, black by default
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+impl HasInputPads for VPad {}
+impl HasOutputPads for VPad {}
+
+impl Filter for VPad {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("pad").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("w", format!("{}", self.width));
+        dict.add("h", format!("{}", self.height));
+
+        if let Some((x, y)) = self.position {
+            dict.add("x", format!("{}", x));
+            dict.add("y", format!("{}", y));
+        } else {
+            dict.add("x", "(ow-iw)/2");
+            dict.add("y", "(oh-ih)/2");
+        }
+
+        dict.add("color", format!("{}", self.color));
+
+        dict
+    }
+}
+
+/// A rotation by a multiple of 90 degrees for the `VTranspose` filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transpose {
+    /// Rotate counter-clockwise and flip vertically
+    CounterClockwiseFlip,
+    /// Rotate clockwise
+    Clockwise,
+    /// Rotate counter-clockwise
+    CounterClockwise,
+    /// Rotate clockwise and flip vertically
+    ClockwiseFlip,
+}
+
+/// Rotate the video by 90 degrees, swapping the width and height
+pub struct VTranspose {
+    name: String,
+    direction: Transpose,
+}
+
+impl VTranspose {
+    pub fn new(name: impl Into<String>, direction: Transpose) -> Self {
+        VTranspose {
+            name: name.into(),
+            direction,
+        }
+    }
+}
+
+impl HasInputPads for VTranspose {}
+impl HasOutputPads for VTranspose {}
+
+impl Filter for VTranspose {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("transpose").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        let dir = match self.direction {
+            Transpose::CounterClockwiseFlip => "cclock_flip",
+            Transpose::Clockwise => "clock",
+            Transpose::CounterClockwise => "cclock",
+            Transpose::ClockwiseFlip => "clock_flip",
+        };
+        dict.add("dir", dir);
+
+        dict
+    }
+}
+
+/// Rotate the video by an arbitrary angle
+///
+/// The output keeps the size of the input unless set with `output_size()`,
+/// areas not covered by the rotated video are filled with the fill color.
+pub struct VRotate {
+    name: String,
+    angle: f64,
+    output_size: Option<(u32, u32)>,
+    fill_color: Option<Color>,
+}
+
+impl VRotate {
+    /// Rotate the video clockwise by `angle` radians
+    pub fn new(name: impl Into<String>, angle: f64) -> Self {
+        VRotate {
+            name: name.into(),
+            angle,
+            output_size: None,
+            fill_color: Some(Color::BLACK),
+        }
+    }
+
+    pub fn output_size(mut self, width: u32, height: u32) -> Self {
+        self.output_size = Some((width, height));
+        self
+    }
+
+    /// Set the color of the uncovered areas, None leaves them unpainted
+    pub fn fill_color(mut self, color: Option<Color>) -> Self {
+        self.fill_color = color;
+        self
+    }
+}
+
+impl HasInputPads for VRotate {}
+impl HasOutputPads for VRotate {}
+
+impl Filter for VRotate {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("rotate").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("angle", format!("{}", self.angle));
+
+        if let Some((width, height)) = self.output_size {
+            dict.add("out_w", format!("{}", width));
+            dict.add("out_h", format!("{}", height));
+        }
+
+        match self.fill_color {
+            Some(color) => dict.add("fillcolor", format!("{}", color)),
+            None => dict.add("fillcolor", "none"),
+        }
+
+        dict
+    }
+}
+
+impl UpdatableFilter for VRotate {
+    /// The new angle in radians
+    type Command = f64;
+
+    fn command_args(angle: &f64) -> (String, String) {
+        ("angle".into(), format!("{}", angle))
+    }
+}
+
+/// Mirror the video horizontally
+pub struct VHFlip {
+    name: String,
+}
+
+impl VHFlip {
+    pub fn new(name: impl Into<String>) -> Self {
+        VHFlip { name: name.into() }
+    }
+}
+
+impl HasInputPads for VHFlip {}
+impl HasOutputPads for VHFlip {}
+
+impl Filter for VHFlip {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("hflip").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        Dictionary::new()
+    }
+}
+
+/// Flip the video upside down
+pub struct VVFlip {
+    name: String,
+}
+
+impl VVFlip {
+    pub fn new(name: impl Into<String>) -> Self {
+        VVFlip { name: name.into() }
+    }
+}
+
+impl HasInputPads for VVFlip {}
+impl HasOutputPads for VVFlip {}
+
+impl Filter for VVFlip {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("vflip").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        Dictionary::new()
+    }
+}
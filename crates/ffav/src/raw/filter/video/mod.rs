@@ -1,7 +1,19 @@
+//! Video specific filter configurations
+//!
+//! REF: https://ffmpeg.org/ffmpeg-filters.html#Video-Filters
+
 mod buffersrc;
+mod color;
+mod compose;
 mod format;
+mod geometry;
 mod sink;
+mod timing;
 
 pub use buffersrc::*;
+pub use color::Color;
+pub use compose::*;
 pub use format::*;
+pub use geometry::*;
 pub use sink::*;
+pub use timing::*;
@@ -1,19 +1,32 @@
 use crate::{
     config::Dictionary,
     error::{Error, Result},
-    raw::filter::{Filter, FilterOutput, HasInputPads},
-    util::marker::Audio,
+    raw::filter::{binary_option_arg, Filter, FilterOutput, HasInputPads},
+    util::{color::PixelFormat, marker::Video},
 };
-use ffav_sys::av_buffersink_get_frame;
+use ffav_sys::{av_buffersink_get_frame, AVPixelFormat};
 use std::ffi::CString;
 
 pub struct VBufferSink {
     name: String,
+    pixel_formats: Vec<PixelFormat>,
 }
 
 impl VBufferSink {
     pub fn new(name: impl Into<String>) -> Self {
-        VBufferSink { name: name.into() }
+        VBufferSink {
+            name: name.into(),
+            pixel_formats: Vec::new(),
+        }
+    }
+
+    /// Only accept frames in one of `formats`
+    ///
+    /// The FilterGraph will convert the video to one of the formats when it
+    /// is configured. By default any pixel format is accepted.
+    pub fn pixel_formats(mut self, formats: Vec<PixelFormat>) -> Self {
+        self.pixel_formats = formats;
+        self
     }
 }
 
@@ -30,14 +43,24 @@ impl Filter for VBufferSink {
     }
 
     fn config_parameters_dict(&self) -> Dictionary {
-        Dictionary::new()
+        let mut dict = Dictionary::new();
+
+        if !self.pixel_formats.is_empty() {
+            let formats = self
+                .pixel_formats
+                .iter()
+                .map(|&format| (AVPixelFormat::from(format) as i32).to_ne_bytes());
+            dict.add("pix_fmts", binary_option_arg(formats));
+        }
+
+        dict
     }
 }
 
 impl HasInputPads for VBufferSink {}
 
 impl FilterOutput for VBufferSink {
-    type StreamType = Audio;
+    type StreamType = Video;
 
     unsafe fn get_frame_into(
         filter: *mut ffav_sys::AVFilterContext,
@@ -53,25 +76,34 @@ impl FilterOutput for VBufferSink {
     }
 }
 
-// pub struct ANullSink {
-//     name: String,
-// }
+/// Discards all video it receives
+///
+/// The video equivalent of `ANullSink`, eg. for an unused branch of a `VSplit`.
+pub struct VNullSink {
+    name: String,
+}
 
-// impl Filter for ANullSink {
-//     fn filter_type_name() -> CString {
-//         // NOTE: This unwrap should never fail because I know this string to be
-//         // a valid C-style string. If this fails something horrible has gone wrong
-//         // and a bug should probably be filed.
-//         CString::new("anullsink").expect("Critical Error")
-//     }
+impl VNullSink {
+    pub fn new(name: impl Into<String>) -> Self {
+        VNullSink { name: name.into() }
+    }
+}
 
-//     fn filter_name(&self) -> &String {
-//         &self.name
-//     }
+impl Filter for VNullSink {
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("nullsink").expect("Critical Error")
+    }
+
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
 
-//     fn config_parameters_dict(&self) -> Dictionary {
-//         Dictionary::new()
-//     }
-// }
+    fn config_parameters_dict(&self) -> Dictionary {
+        Dictionary::new()
+    }
+}
 
-// impl HasInputPads for ANullSink {}
+impl HasInputPads for VNullSink {}
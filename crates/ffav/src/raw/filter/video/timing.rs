@@ -0,0 +1,188 @@
+use std::ffi::CString;
+
+use crate::{
+    config::Dictionary,
+    raw::filter::{Filter, HasInputPads, HasOutputPads},
+    util::time::{FrameRate, Timestamp},
+};
+
+/// Convert the video to a constant frame-rate by duplicating or dropping frames
+pub struct VFps {
+    name: String,
+    frame_rate: FrameRate,
+}
+
+impl VFps {
+    pub fn new(name: impl Into<String>, frame_rate: FrameRate) -> Self {
+        VFps {
+            name: name.into(),
+            frame_rate,
+        }
+    }
+}
+
+impl HasInputPads for VFps {}
+impl HasOutputPads for VFps {}
+
+impl Filter for VFps {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("fps").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("fps", format!("{}", self.frame_rate));
+
+        dict
+    }
+}
+
+/// Change the presentation timestamps of the video frames
+pub struct VSetPts {
+    name: String,
+    expr: String,
+}
+
+impl VSetPts {
+    /// Shift the timestamps so the first frame starts at zero
+    pub fn zero_based(name: impl Into<String>) -> Self {
+        Self::expression(name, "PTS-STARTPTS")
+    }
+
+    /// Shift the timestamps later by `offset`, a negative offset shifts them
+    /// earlier
+    pub fn offset(name: impl Into<String>, offset: impl Into<Timestamp>) -> Self {
+        let offset = offset.into().as_secs_f64();
+
+        Self::expression(name, format!("PTS+{}/TB", offset))
+    }
+
+    /// Play the video `factor` times faster, a factor below 1.0 slows it down
+    ///
+    /// # Panics
+    /// If `factor` isn't a finite number greater than zero
+    pub fn speed(name: impl Into<String>, factor: f64) -> Self {
+        assert!(
+            factor.is_finite() && factor > 0.0,
+            "The speed factor must be finite and greater than zero"
+        );
+
+        Self::expression(name, format!("PTS/{}", factor))
+    }
+
+    /// Calculate the timestamps with a custom expression
+    ///
+    /// REF: https://ffmpeg.org/ffmpeg-filters.html#setpts_002c-asetpts
+    pub fn expression(name: impl Into<String>, expr: impl Into<String>) -> Self {
+        VSetPts {
+            name: name.into(),
+            expr: expr.into(),
+        }
+    }
+}
+
+impl HasInputPads for VSetPts {}
+impl HasOutputPads for VSetPts {}
+
+impl Filter for VSetPts {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("setpts").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("expr", &self.expr);
+
+        dict
+    }
+}
+
+/// Keep only the frames within a section of the video
+pub struct VTrim {
+    name: String,
+    start: Timestamp,
+    end: TrimEnd,
+}
+
+/// Where the section kept by `VTrim` ends
+enum TrimEnd {
+    End(Timestamp),
+    Duration(Timestamp),
+}
+
+impl VTrim {
+    /// Keep the frames from `start` until `duration` has passed
+    ///
+    /// `start` is relative to the timestamps of the input frames.
+    pub fn new_with_duration(
+        name: impl Into<String>,
+        start: impl Into<Timestamp>,
+        duration: impl Into<Timestamp>,
+    ) -> Self {
+        VTrim {
+            name: name.into(),
+            start: start.into(),
+            end: TrimEnd::Duration(duration.into()),
+        }
+    }
+
+    /// Keep the frames from `start` up to, but not including, `end`
+    ///
+    /// Both times are relative to the timestamps of the input frames.
+    pub fn new_with_end(
+        name: impl Into<String>,
+        start: impl Into<Timestamp>,
+        end: impl Into<Timestamp>,
+    ) -> Self {
+        VTrim {
+            name: name.into(),
+            start: start.into(),
+            end: TrimEnd::End(end.into()),
+        }
+    }
+}
+
+impl HasInputPads for VTrim {}
+impl HasOutputPads for VTrim {}
+
+impl Filter for VTrim {
+    fn filter_name(&self) -> &String {
+        &self.name
+    }
+
+    fn filter_type_name() -> CString {
+        // NOTE: This unwrap should never fail because I know this string to be
+        // a valid C-style string. If this fails something horrible has gone wrong
+        // and a bug should probably be filed.
+        CString::new("trim").expect("Critical Error")
+    }
+
+    fn config_parameters_dict(&self) -> Dictionary {
+        let mut dict = Dictionary::new();
+
+        dict.add("start", format!("{}", self.start));
+
+        match self.end {
+            TrimEnd::End(end) => dict.add("end", format!("{}", end)),
+            TrimEnd::Duration(dur) => dict.add("duration", format!("{}", dur)),
+        }
+
+        dict
+    }
+}
@@ -1,21 +1,29 @@
 use crate::{
-    config::{FormatConfig, StreamConfig},
+    config::{Dictionary, FormatConfig, StreamConfig},
     error::{Error, Result},
-    raw::stream::Stream,
+    raw::{codec::Codec, io::IoContext, stream::Stream},
     util::{
-        marker::{Input, Output, Unknown},
+        marker::{Encode, Input, Output, Unknown},
         path_to_cstr,
         time::{IntoStreamTimestamp, TimeBase, Timestamp},
         MediaType,
     },
 };
 use ffav_sys::{
-    av_find_best_stream, av_read_frame, av_seek_frame, avformat_close_input,
-    avformat_find_stream_info, avformat_free_context, avformat_open_input,
-    err::{AVERROR_DECODER_NOT_FOUND, AVERROR_STREAM_NOT_FOUND},
-    AVFormatContext, AVSEEK_FLAG_BACKWARD,
+    av_find_best_stream, av_interleaved_write_frame, av_read_frame, av_seek_frame,
+    av_write_trailer, avcodec_parameters_copy, avcodec_parameters_from_context,
+    avformat_alloc_context, avformat_alloc_output_context2, avformat_close_input,
+    avformat_find_stream_info, avformat_free_context, avformat_new_stream, avformat_open_input,
+    avformat_write_header, avio_closep, avio_open,
+    err::{av_err, AVERROR_DECODER_NOT_FOUND, AVERROR_STREAM_NOT_FOUND, EINVAL, EPIPE, ESPIPE},
+    AVFormatContext, AVStream, AVFMT_FLAG_CUSTOM_IO, AVFMT_GLOBALHEADER, AVFMT_NOFILE,
+    AVIO_FLAG_WRITE, AVSEEK_FLAG_BACKWARD,
+};
+use std::{
+    ffi::{CStr, CString},
+    io::{Cursor, Read, Seek, Write},
+    path::Path,
 };
-use std::path::Path;
 
 use super::packet::Packet;
 
@@ -27,6 +35,12 @@ use super::packet::Packet;
 pub struct Format<IO> {
     ctx: *mut AVFormatContext,
     config: FormatConfig<IO>,
+    // Only used by output Formats, set when the header has been written and
+    // the trailer still needs to be written to finalize the output
+    trailer_pending: bool,
+    // Custom I/O used instead of a file, this must be dropped after the
+    // format context which is guaranteed by being a member of this struct
+    io: Option<IoContext>,
 }
 
 impl<T> Format<T> {
@@ -47,6 +61,42 @@ impl<T> Format<T> {
     pub fn get_configuration(&self) -> &FormatConfig<T> {
         &self.config
     }
+
+    /// Get all the streams contained in the Format
+    ///
+    /// The returned streams are of an unknown type and will have to be converted appropriately
+    pub fn streams(&self) -> &'_ [Stream<'_, Unknown>] {
+        unsafe {
+            // This should never happen so only assert in debug mode
+            debug_assert!(!self.ctx.is_null(), "An invalid NULL format was used");
+            // Output formats won't have a streams array until one is added
+            if (*self.ctx).nb_streams == 0 {
+                return &[];
+            }
+            // This could potentially happen in some pathological case, so panic
+            // in release if this is the case
+            assert!(
+                !(*self.ctx).streams.is_null(),
+                "Streams member of the format was NULL"
+            );
+
+            let streams =
+                std::slice::from_raw_parts((*self.ctx).streams, (*self.ctx).nb_streams as usize);
+
+            assert!(
+                streams.iter().all(|ptr| { !ptr.is_null() }),
+                "One of the streams in the specified size was NULL, corrupted format?"
+            );
+
+            // The UCG says that this transmute *should be* sound as long as the `Stream` struct
+            // only contains the pointer and the 1-ZST:
+            // https://rust-lang.github.io/unsafe-code-guidelines/layout/structs-and-tuples.html#structs-with-1-zst-fields
+            // So it should be the same size and alignment as the underlying pointer.
+            static_assertions::assert_eq_size!(*mut ffav_sys::AVStream, Stream<Unknown>);
+            static_assertions::assert_eq_align!(*mut ffav_sys::AVStream, Stream<Unknown>);
+            std::mem::transmute(streams)
+        }
+    }
 }
 
 impl Format<Input> {
@@ -70,16 +120,87 @@ impl Format<Input> {
                 return Err(Error::from_av_err("opening input format", ret));
             }
 
-            let ret = avformat_find_stream_info(ctx, std::ptr::null_mut());
+            Self::from_opened_input(ctx, None)
+        }
+    }
+
+    /// Open an input Format which reads its data from the provided reader
+    ///
+    /// The format type will be determined by probing the data. The reader is
+    /// owned by the Format and will be dropped along with it, so it can't
+    /// borrow data with a non-`'static` lifetime.
+    pub fn open_reader<R: Read + Seek + Send + 'static>(reader: R) -> Result<Format<Input>> {
+        Self::open_io(IoContext::reader(reader)?)
+    }
+
+    /// Open an input Format which reads its data from a reader that cannot seek
+    /// (eg. a pipe or socket)
+    ///
+    /// Some formats cannot be read without seeking, and seeking the resulting
+    /// Format will fail.
+    pub fn open_nonseekable_reader<R: Read + Send + 'static>(reader: R) -> Result<Format<Input>> {
+        Self::open_io(IoContext::nonseekable_reader(reader)?)
+    }
+
+    /// Open an input Format from data held in memory (eg. a `Vec<u8>`)
+    ///
+    /// The buffer is owned by the Format, so a borrowed `&[u8]` is only
+    /// accepted if it is `'static`. To share data with the rest of the
+    /// program without copying it, pass an `Arc<[u8]>`.
+    pub fn open_buffer<B: AsRef<[u8]> + Send + 'static>(buffer: B) -> Result<Format<Input>> {
+        Self::open_reader(Cursor::new(buffer))
+    }
+
+    fn open_io(io: IoContext) -> Result<Format<Input>> {
+        unsafe {
+            let mut ctx = avformat_alloc_context();
+
+            if ctx.is_null() {
+                return Err(Error::AllocationFailed("creating input format"));
+            }
+
+            (*ctx).pb = io.as_raw();
+            (*ctx).flags |= AVFMT_FLAG_CUSTOM_IO;
+
+            // NOTE: On failure the context is freed by `avformat_open_input()`
+            // but the I/O context is still ours and will be dropped here
+            let ret = avformat_open_input(
+                &mut ctx,
+                std::ptr::null(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            );
 
             if ret < 0 {
-                return Err(Error::from_av_err("getting stream info", ret));
+                return Err(Error::from_av_err("opening input format", ret));
             }
 
-            let config = FormatConfig::from_input_fmt_ctx(ctx);
+            Self::from_opened_input(ctx, Some(io))
+        }
+    }
 
-            Ok(Format { ctx, config })
+    /// Shared setup for a format context which was opened successfully
+    unsafe fn from_opened_input(
+        ctx: *mut AVFormatContext,
+        io: Option<IoContext>,
+    ) -> Result<Format<Input>> {
+        // Take ownership first so the context is closed if probing fails
+        let mut fmt = Format {
+            ctx,
+            config: FormatConfig::from_input_fmt_ctx(ctx),
+            trailer_pending: false,
+            io,
+        };
+
+        let ret = avformat_find_stream_info(fmt.ctx, std::ptr::null_mut());
+
+        if ret < 0 {
+            return Err(Error::from_av_err("getting stream info", ret));
         }
+
+        fmt.config = FormatConfig::from_input_fmt_ctx(fmt.ctx);
+
+        Ok(fmt)
     }
 
     /// Tries to get the "best" stream for the requested type
@@ -104,38 +225,6 @@ impl Format<Input> {
         }
     }
 
-    /// Get all the streams contained in the Format
-    ///
-    /// The returned streams are of an unknown type and will have to be converted appropriately
-    pub fn streams(&self) -> &'_ [Stream<'_, Unknown>] {
-        unsafe {
-            // This should never happen so only assert in debug mode
-            debug_assert!(!self.ctx.is_null(), "An invalid NULL format was used");
-            // This could potentially happen in some pathological case, so panic
-            // in release if this is the case
-            assert!(
-                !(*self.ctx).streams.is_null(),
-                "Streams member of the format was NULL"
-            );
-
-            let streams =
-                std::slice::from_raw_parts((*self.ctx).streams, (*self.ctx).nb_streams as usize);
-
-            assert!(
-                streams.iter().all(|ptr| { !ptr.is_null() }),
-                "One of the streams in the specified size was NULL, corrupted format?"
-            );
-
-            // The UCG says that this transmute *should be* sound as long as the `Stream` struct
-            // only contains the pointer and the 1-ZST:
-            // https://rust-lang.github.io/unsafe-code-guidelines/layout/structs-and-tuples.html#structs-with-1-zst-fields
-            // So it should be the same size and alignment as the underlying pointer.
-            static_assertions::assert_eq_size!(*mut ffav_sys::AVStream, Stream<Unknown>);
-            static_assertions::assert_eq_align!(*mut ffav_sys::AVStream, Stream<Unknown>);
-            std::mem::transmute(streams)
-        }
-    }
-
     /// Get the next packet from the Format and place it in the provided packet object
     ///
     /// Note: Existing data will be unreferenced from the packet
@@ -169,7 +258,7 @@ impl Format<Input> {
         stream: &StreamConfig<T>,
         ts: impl IntoStreamTimestamp<T>,
     ) -> Result<()> {
-        self.inner_seek(stream.stream_index() as i32, ts.into(stream), true)
+        self.inner_seek(stream.stream_index() as i32, ts.into(stream)?, true)
     }
 
     /// Seek to the nearest frame after the specified timestamp
@@ -181,7 +270,7 @@ impl Format<Input> {
         stream: &StreamConfig<T>,
         ts: impl IntoStreamTimestamp<T>,
     ) -> Result<()> {
-        self.inner_seek(stream.stream_index() as i32, ts.into(stream), false)
+        self.inner_seek(stream.stream_index() as i32, ts.into(stream)?, false)
     }
 
     /// Seek to the nearest frame before the specified timestamp
@@ -233,13 +322,323 @@ impl Format<Output> {
     ///
     /// The format type will be automatically determined by the file extension
     /// provided
-    pub fn open_output<P: AsRef<Path>>(_file: P) -> Result<Format<Output>> {
-        unimplemented!()
+    pub fn open_output<P: AsRef<Path>>(file: P) -> Result<Format<Output>> {
+        Self::inner_open_output(file.as_ref(), None)
+    }
+
+    /// Open a file as an output target with a specific format type
+    ///
+    /// The `format_name` is the short name of the muxer as used by the ffmpeg
+    /// CLI (eg. "matroska", "mp4", "wav").
+    pub fn open_with_format_type<P: AsRef<Path>>(
+        file: P,
+        format_name: &str,
+    ) -> Result<Format<Output>> {
+        Self::inner_open_output(file.as_ref(), Some(format_name))
+    }
+
+    /// Open an output Format which writes its data to the provided writer
+    ///
+    /// As there is no file name to guess from, the `format_name` of the muxer
+    /// must be provided (eg. "matroska", "mpegts"). The writer is owned by the
+    /// Format and can be retrieved with `finish_into_writer()`.
+    ///
+    /// Some muxers need to seek back to finalize their output and will fail
+    /// with `Error::OutputNotSeekable` when they try to. Others, like "mp4",
+    /// check when the header is written and fail with `Error::InvalidArguments`.
+    /// For these use `open_seekable_writer()` or configure the muxer for
+    /// streaming (eg. `movflags=frag_keyframe+empty_moov` for "mp4").
+    pub fn open_writer<W: Write + Send + 'static>(
+        writer: W,
+        format_name: &str,
+    ) -> Result<Format<Output>> {
+        Self::open_io(IoContext::writer(writer)?, format_name)
+    }
+
+    /// Open an output Format which writes its data to a writer that can seek
+    /// (eg. a `File` or `Cursor<Vec<u8>>`)
+    ///
+    /// See `open_writer()` for details.
+    pub fn open_seekable_writer<W: Write + Seek + Send + 'static>(
+        writer: W,
+        format_name: &str,
+    ) -> Result<Format<Output>> {
+        Self::open_io(IoContext::seekable_writer(writer)?, format_name)
+    }
+
+    fn open_io(io: IoContext, format_name: &str) -> Result<Format<Output>> {
+        let cformat_name = CString::new(format_name)?;
+        let mut fmt = Self::alloc_output(Some(&cformat_name), None)?;
+
+        unsafe {
+            (*fmt.ctx).pb = io.as_raw();
+            (*fmt.ctx).flags |= AVFMT_FLAG_CUSTOM_IO;
+        }
+
+        fmt.io = Some(io);
+
+        Ok(fmt)
+    }
+
+    fn inner_open_output(file: &Path, format_name: Option<&str>) -> Result<Format<Output>> {
+        let cfile_path = path_to_cstr(file)?;
+        let cformat_name = format_name.map(CString::new).transpose()?;
+
+        // From here on the `Format` owns the context so it will be cleaned
+        // up if opening the file fails
+        let fmt = Self::alloc_output(cformat_name.as_deref(), Some(&cfile_path))?;
+
+        unsafe {
+            // Some formats (eg. image sequences) handle their own I/O
+            if (*(*fmt.ctx).oformat).flags & AVFMT_NOFILE == 0 {
+                let ret = avio_open(&mut (*fmt.ctx).pb, cfile_path.as_ptr(), AVIO_FLAG_WRITE);
+
+                if ret < 0 {
+                    return Err(Error::from_av_err("opening output file", ret));
+                }
+            }
+        }
+
+        Ok(fmt)
+    }
+
+    /// Allocate an output format context, the muxer is chosen by `format_name`
+    /// if provided and otherwise guessed from the `file_name`
+    fn alloc_output(
+        format_name: Option<&CStr>,
+        file_name: Option<&CStr>,
+    ) -> Result<Format<Output>> {
+        unsafe {
+            let mut ctx = std::ptr::null_mut();
+
+            let ret = avformat_alloc_output_context2(
+                &mut ctx,
+                std::ptr::null_mut(),
+                format_name.map_or(std::ptr::null(), |name| name.as_ptr()),
+                file_name.map_or(std::ptr::null(), |name| name.as_ptr()),
+            );
+
+            // EINVAL is returned when no muxer matches the name
+            if ret == av_err(EINVAL) {
+                return Err(Error::ResourceNotFound("output format"));
+            } else if ret < 0 {
+                return Err(Error::from_av_err("allocating output format", ret));
+            } else if ctx.is_null() {
+                return Err(Error::AllocationFailed("output format"));
+            }
+
+            Ok(Format {
+                ctx,
+                config: FormatConfig::from_output_fmt_ctx(ctx),
+                trailer_pending: false,
+                io: None,
+            })
+        }
+    }
+
+    /// Add a new stream to the output which copies the codec parameters of an
+    /// existing stream, typically from an input `Format`.
+    ///
+    /// This is used to copy packets between Formats without re-encoding them.
+    /// Returns the index of the new stream in this Format.
+    pub fn add_stream_from<AV>(&mut self, stream: &Stream<'_, AV>) -> Result<usize> {
+        unsafe {
+            let src = stream.as_raw();
+            let dest = self.inner_add_stream()?;
+
+            let err = avcodec_parameters_copy((*dest).codecpar, (*src).codecpar);
+
+            if err < 0 {
+                return Err(Error::from_av_err("copying codec parameters", err));
+            }
+
+            // The codec tag is specific to the container the stream came from
+            // so let the muxer choose an appropriate one
+            (*(*dest).codecpar).codec_tag = 0;
+            // This is only a hint, the muxer may choose a different time base
+            // when the header is written
+            (*dest).time_base = (*src).time_base;
+
+            Ok((*dest).index as usize)
+        }
+    }
+
+    /// Add a new stream to the output which will contain the packets produced
+    /// by the provided encoder.
+    ///
+    /// Returns the index of the new stream in this Format.
+    pub fn add_stream_for_encoder<AV>(&mut self, codec: &Codec<Encode, AV>) -> Result<usize> {
+        unsafe {
+            let enc = codec.as_raw();
+            let dest = self.inner_add_stream()?;
+
+            let err = avcodec_parameters_from_context((*dest).codecpar, enc);
+
+            if err < 0 {
+                return Err(Error::from_av_err("copying encoder parameters", err));
+            }
+
+            // This is only a hint, the muxer may choose a different time base
+            // when the header is written
+            (*dest).time_base = (*enc).time_base;
+
+            Ok((*dest).index as usize)
+        }
+    }
+
+    /// Shared inner function for creating a new stream, the caller is
+    /// responsible for setting up the codec parameters of the stream.
+    unsafe fn inner_add_stream(&mut self) -> Result<*mut AVStream> {
+        if self.trailer_pending {
+            return Err(Error::HeaderAlreadyWritten);
+        }
+
+        let stream = avformat_new_stream(self.ctx, std::ptr::null());
+
+        if stream.is_null() {
+            return Err(Error::AllocationFailed("creating output stream"));
+        }
+
+        self.config = FormatConfig::from_output_fmt_ctx(self.ctx);
+
+        Ok(stream)
+    }
+
+    /// Write the header of the output Format
+    ///
+    /// All streams must be added before the header is written. The muxer may
+    /// change the TimeBase of the streams when writing the header so stream
+    /// configurations should be retrieved after this call.
+    pub fn write_header(&mut self) -> Result<()> {
+        self.write_header_with_options(&mut Dictionary::new())
+    }
+
+    /// Write the header of the output Format providing options to the muxer
+    ///
+    /// On return the `options` dictionary will contain only the options which
+    /// were not recognized by the muxer.
+    pub fn write_header_with_options(&mut self, options: &mut Dictionary) -> Result<()> {
+        if self.trailer_pending {
+            return Err(Error::HeaderAlreadyWritten);
+        }
+
+        unsafe {
+            let err = avformat_write_header(self.ctx, options.as_dict());
+
+            // NOTE: Some muxers which require seeking, eg. mp4 without
+            // fragmentation, reject non-seekable output up front with the same
+            // EINVAL used for invalid options, so that can't be reported as
+            // `Error::OutputNotSeekable`
+            if err < 0 {
+                return Err(self.output_error("writing format header", err));
+            }
+        }
+
+        self.trailer_pending = true;
+
+        Ok(())
+    }
+
+    /// Write a packet to the output Format
+    ///
+    /// Packets are buffered and interleaved between streams as required by the
+    /// muxer. The packet's stream index must refer to a stream of this Format and
+    /// its timestamps must be in the TimeBase of that stream.
+    ///
+    /// The data of the packet will be taken by the Format and the packet will be
+    /// available for later use.
+    pub fn write_packet(&mut self, packet: &mut Packet) -> Result<()> {
+        if !self.trailer_pending {
+            return Err(Error::HeaderNotWritten);
+        }
+
+        let index = packet.stream_index();
+        if index >= self.config.num_streams() {
+            return Err(Error::InvalidStreamIndex(index));
+        }
+
+        unsafe {
+            let err = av_interleaved_write_frame(self.ctx, packet.as_raw());
+
+            if err < 0 {
+                return Err(self.output_error("writing packet to format", err));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finalize the output by flushing all buffered packets and writing the
+    /// trailer of the Format
+    ///
+    /// Dropping an output Format will also write the trailer, but any errors
+    /// will be silently ignored.
+    pub fn finish(mut self) -> Result<()> {
+        self.write_trailer()
+    }
+
+    /// Finalize the output like `finish()` and return the writer the Format was
+    /// opened with
+    ///
+    /// `W` must be the type of the writer passed to `open_writer()` or
+    /// `open_seekable_writer()`, otherwise `Error::WriterTypeMismatch` is
+    /// returned.
+    pub fn finish_into_writer<W: 'static>(mut self) -> Result<W> {
+        if !self.io.as_ref().map_or(false, IoContext::holds::<W>) {
+            return Err(Error::WriterTypeMismatch);
+        }
+
+        self.write_trailer()?;
+
+        // The I/O context must outlive the format context which refers to it
+        let io = self.io.take();
+        drop(self);
+
+        io.and_then(IoContext::into_inner)
+            .ok_or(Error::WriterTypeMismatch)
+    }
+
+    fn write_trailer(&mut self) -> Result<()> {
+        if !self.trailer_pending {
+            return Err(Error::HeaderNotWritten);
+        }
+
+        self.trailer_pending = false;
+
+        unsafe {
+            let err = av_write_trailer(self.ctx);
+
+            if err < 0 {
+                return Err(self.output_error("writing format trailer", err));
+            }
+        }
+
+        // Data may still be buffered in the writer, eg. if it is a `BufWriter`
+        if let Some(io) = &mut self.io {
+            io.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn has_nonseekable_io(&self) -> bool {
+        self.io.as_ref().map_or(false, |io| !io.is_seekable())
+    }
+
+    /// Convert an error from the muxer, attempts to seek a writer which can't
+    /// are reported as `Error::OutputNotSeekable`
+    fn output_error(&self, ctx: &'static str, err: i32) -> Error {
+        if self.has_nonseekable_io() && (err == av_err(EPIPE) || err == av_err(ESPIPE)) {
+            Error::OutputNotSeekable
+        } else {
+            Error::from_av_err(ctx, err)
+        }
     }
 
-    /// Open a filoe as an output target with a specific format type
-    pub fn open_with_format_type(_info: ()) -> Result<Format<Output>> {
-        unimplemented!()
+    /// Check if the output Format requires codecs to place global headers in
+    /// the stream's extradata rather than in every keyframe
+    pub fn needs_global_header(&self) -> bool {
+        unsafe { (*(*self.ctx).oformat).flags & AVFMT_GLOBALHEADER != 0 }
     }
 }
 
@@ -253,6 +652,22 @@ impl<T> std::ops::Drop for Format<T> {
                 let input_format = (*self.ctx).iformat;
                 if !input_format.is_null() {
                     avformat_close_input(&mut self.ctx);
+                } else {
+                    // Output types need to be finalized if a header was written
+                    // there is nothing useful to do with an error here
+                    if self.trailer_pending {
+                        av_write_trailer(self.ctx);
+                        self.trailer_pending = false;
+                    }
+
+                    // Custom I/O is closed when the `IoContext` is dropped
+                    let output_format = (*self.ctx).oformat;
+                    if !output_format.is_null()
+                        && (*output_format).flags & AVFMT_NOFILE == 0
+                        && (*self.ctx).flags & AVFMT_FLAG_CUSTOM_IO == 0
+                    {
+                        avio_closep(&mut (*self.ctx).pb);
+                    }
                 }
             }
 
@@ -264,6 +679,9 @@ impl<T> std::ops::Drop for Format<T> {
 }
 
 // SAFETY: Format<T> has no interior mutabillity and is the single owner of the
-// pointer contained within.
+// pointer contained within. Custom I/O objects are required to be `Send`.
 unsafe impl<T> std::marker::Send for Format<T> {}
+// SAFETY: No method taking `&self` reads, seeks or writes through the
+// AVIOContext or the custom I/O object, so they are only ever used through
+// `&mut self` and don't need to be `Sync`.
 unsafe impl<T> std::marker::Sync for Format<T> {}
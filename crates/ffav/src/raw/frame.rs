@@ -1,7 +1,23 @@
-use ffav_sys::{av_frame_alloc, av_frame_free, av_frame_unref, AVFrame, AV_NUM_DATA_POINTERS};
-use std::{marker::PhantomData, ops::Deref};
+use ffav_sys::{
+    av_frame_alloc, av_frame_free, av_frame_get_buffer, av_frame_make_writable, av_frame_unref,
+    av_pix_fmt_count_planes, av_pix_fmt_desc_get, av_sample_fmt_is_planar, AVColorRange,
+    AVColorSpace, AVFrame, AVPixelFormat, AVSampleFormat, AV_NUM_DATA_POINTERS,
+};
+use std::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
 
-use crate::util::{marker::Video, time::TimeBaseTicks};
+use crate::{
+    error::{Error, Result},
+    util::{
+        channels::ChannelLayout,
+        color::{ColorRange, ColorSpace, PixelFormat},
+        marker::{Audio, Video},
+        sampling::SampleFormat,
+        time::{SampleRate, TimeBaseTicks},
+    },
+};
 
 pub const NUM_DATA_PLANES: usize = AV_NUM_DATA_POINTERS as usize;
 
@@ -43,8 +59,37 @@ impl<AV> Frame<AV> {
     }
 
     /// Get the expected presentation time of this frame
-    pub fn get_pts(&self) -> TimeBaseTicks {
-        unsafe { TimeBaseTicks::new((*self.frame).pts as u64) }
+    ///
+    /// Will return None if the presentation time is unknown
+    pub fn get_pts(&self) -> Option<TimeBaseTicks> {
+        unsafe { TimeBaseTicks::from_av_timestamp((*self.frame).pts) }
+    }
+
+    /// Get the presentation time of this frame as estimated by the decoder
+    ///
+    /// This is often available for decoded frames when `get_pts()` is not.
+    pub fn get_best_effort_timestamp(&self) -> Option<TimeBaseTicks> {
+        unsafe { TimeBaseTicks::from_av_timestamp((*self.frame).best_effort_timestamp) }
+    }
+
+    /// Get the duration of this frame in the same TimeBase as its timestamps
+    ///
+    /// Will return None if the duration is unknown
+    pub fn get_duration(&self) -> Option<TimeBaseTicks> {
+        match unsafe { (*self.frame).pkt_duration } {
+            duration if duration > 0 => Some(TimeBaseTicks::new(duration)),
+            _ => None,
+        }
+    }
+
+    /// Set the expected presentation time of this frame
+    ///
+    /// When submitting frames to an encoder this should be in the TimeBase
+    /// of the encoder.
+    pub fn set_pts(&mut self, pts: Option<TimeBaseTicks>) {
+        unsafe {
+            (*self.frame).pts = TimeBaseTicks::option_as_av_timestamp(pts);
+        }
     }
 
     /// Get the raw pointer to the frame
@@ -60,6 +105,12 @@ impl<AV> Frame<AV> {
         self.frame
     }
 
+    /// Get a read-only raw pointer to the frame for libav functions which
+    /// don't modify it
+    pub(crate) fn as_ptr(&self) -> *const AVFrame {
+        self.frame
+    }
+
     /// Transform this Frame from its current type to another type
     ///
     /// Existing data in this frame will be unreferenced and no longer
@@ -77,27 +128,136 @@ impl<AV> Frame<AV> {
 
     /// Get a read-only slice of the specified plane
     ///
+    /// Returns `Error::PlaneDoesntExist` if the frame's format doesn't use the
+    /// plane or the frame doesn't hold any data.
+    ///
     /// # Panics
     /// Plane must be less than `NUM_DATA_PLANES` or this function
     /// will panic
-    pub fn plane(&self, plane: usize) -> Plane<'_> {
-        if plane > NUM_DATA_PLANES {
-            panic!("The requested plane is outside the range supported by this version of ffmpeg");
+    pub fn plane(&self, plane: usize) -> Result<Plane<'_>> {
+        let len = self.plane_len(plane)?;
+
+        unsafe {
+            Ok(Plane {
+                data: std::slice::from_raw_parts((*self.frame).data[plane], len),
+                linesize: (*self.frame).linesize[plane] as usize,
+            })
         }
+    }
+
+    /// Get a mutable slice of the specified plane
+    ///
+    /// If the frame data is shared with other frames it is copied first so
+    /// that the changes are only visible through this frame. Returns
+    /// `Error::PlaneDoesntExist` if the frame's format doesn't use the plane
+    /// or the frame doesn't hold any data.
+    ///
+    /// # Panics
+    /// Plane must be less than `NUM_DATA_PLANES` or this function
+    /// will panic
+    pub fn plane_mut(&mut self, plane: usize) -> Result<PlaneMut<'_>> {
+        // Check the plane before making the frame writable, the copy has the
+        // same layout
+        self.plane_len(plane)?;
 
         unsafe {
-            Plane {
-                data: std::slice::from_raw_parts(
-                    (*self.frame).data[plane],
-                    (*self.frame).linesize[plane] as usize * (*self.frame).height as usize,
-                ),
+            let err = av_frame_make_writable(self.frame);
+            if err < 0 {
+                return Err(Error::from_av_err("making frame writable", err));
+            }
+
+            let len = self.plane_len(plane)?;
+
+            Ok(PlaneMut {
+                data: std::slice::from_raw_parts_mut((*self.frame).data[plane], len),
                 linesize: (*self.frame).linesize[plane] as usize,
+            })
+        }
+    }
+
+    /// Get the length in bytes of the specified plane
+    ///
+    /// # Panics
+    /// Plane must be less than `NUM_DATA_PLANES` or this function
+    /// will panic
+    fn plane_len(&self, plane: usize) -> Result<usize> {
+        if plane >= NUM_DATA_PLANES {
+            panic!("The requested plane is outside the range supported by this version of ffmpeg");
+        }
+
+        unsafe {
+            let frame = &*self.frame;
+
+            // Images stored bottom-up have a negative linesize, they can't be
+            // represented as a slice starting at the data pointer
+            if frame.data[plane].is_null() || frame.linesize[plane] < 0 || frame.format < 0 {
+                return Err(Error::PlaneDoesntExist(plane));
             }
+
+            if frame.nb_samples > 0 {
+                // SAFETY: Frames holding samples are audio frames, whose format
+                // is always a valid `AVSampleFormat`
+                let format = std::mem::transmute::<i32, AVSampleFormat>(frame.format);
+
+                // Planar audio has one plane per channel, all of them the size
+                // given by the first linesize
+                let planes = if av_sample_fmt_is_planar(format) != 0 {
+                    frame.channels.max(0) as usize
+                } else {
+                    1
+                };
+
+                if plane >= planes || frame.linesize[0] < 0 {
+                    return Err(Error::PlaneDoesntExist(plane));
+                }
+
+                return Ok(frame.linesize[0] as usize);
+            }
+
+            // SAFETY: Frames without samples are video frames, whose format
+            // is always a valid `AVPixelFormat`
+            let format = std::mem::transmute::<i32, AVPixelFormat>(frame.format);
+            let desc = av_pix_fmt_desc_get(format);
+
+            if desc.is_null() || plane as i32 >= av_pix_fmt_count_planes(format) {
+                return Err(Error::PlaneDoesntExist(plane));
+            }
+
+            // The chroma planes are subsampled vertically, rounding up
+            let height = frame.height.max(0) as usize;
+            let rows = if plane == 1 || plane == 2 {
+                let shift = (*desc).log2_chroma_h;
+                (height + (1 << shift) - 1) >> shift
+            } else {
+                height
+            };
+
+            Ok(frame.linesize[plane] as usize * rows)
         }
     }
 }
 
 impl Frame<Video> {
+    /// Allocate a frame holding a `width` x `height` image in the pixel `format`
+    ///
+    /// The contents of the image are uninitialized.
+    pub fn new_video(width: u32, height: u32, format: PixelFormat) -> Result<Frame<Video>> {
+        let frame = Frame::new();
+
+        unsafe {
+            (*frame.frame).width = width as i32;
+            (*frame.frame).height = height as i32;
+            (*frame.frame).format = AVPixelFormat::from(format) as i32;
+
+            let err = av_frame_get_buffer(frame.frame, 0);
+            if err < 0 {
+                return Err(Error::from_av_err("allocating video frame", err));
+            }
+        }
+
+        Ok(frame)
+    }
+
     /// Get the width of this frame
     pub fn width(&self) -> u32 {
         unsafe { (*self.frame).width as u32 }
@@ -106,6 +266,76 @@ impl Frame<Video> {
     pub fn height(&self) -> u32 {
         unsafe { (*self.frame).height as u32 }
     }
+
+    /// Get the pixel format of this frame
+    ///
+    /// Will return `PixelFormat::NONE` if the frame doesn't hold any data
+    pub fn pixel_format(&self) -> PixelFormat {
+        // SAFETY: The format of a video frame is always set by libav to a
+        // valid `AVPixelFormat`, with -1 (`AV_PIX_FMT_NONE`) for empty frames
+        unsafe {
+            PixelFormat::from(std::mem::transmute::<i32, AVPixelFormat>(
+                (*self.frame).format,
+            ))
+        }
+    }
+
+    /// Get the color space of this frame
+    pub fn color_space(&self) -> ColorSpace {
+        unsafe { ColorSpace::from((*self.frame).colorspace) }
+    }
+
+    /// Set the color space of this frame
+    pub fn set_color_space(&mut self, space: ColorSpace) {
+        unsafe {
+            (*self.frame).colorspace = AVColorSpace::from(space);
+        }
+    }
+
+    /// Get the range of the color values of this frame
+    pub fn color_range(&self) -> ColorRange {
+        unsafe { ColorRange::from((*self.frame).color_range) }
+    }
+
+    /// Set the range of the color values of this frame
+    pub fn set_color_range(&mut self, range: ColorRange) {
+        unsafe {
+            (*self.frame).color_range = AVColorRange::from(range);
+        }
+    }
+}
+
+impl Frame<Audio> {
+    /// Get the number of samples (per channel) held by this frame
+    pub fn num_samples(&self) -> u32 {
+        unsafe { (*self.frame).nb_samples as u32 }
+    }
+
+    /// Get the sample rate of this frame
+    pub fn sample_rate(&self) -> SampleRate {
+        unsafe { SampleRate::new((*self.frame).sample_rate as u32) }
+    }
+
+    /// Get the format of the samples in this frame
+    ///
+    /// Will return None if the frame doesn't hold any data
+    pub fn sample_format(&self) -> Option<SampleFormat> {
+        unsafe {
+            match (*self.frame).format {
+                fmt if fmt < 0 => None,
+                // SAFETY: The format of an audio frame is always set by libav
+                // to a valid `AVSampleFormat`
+                fmt => Some(SampleFormat::from(
+                    std::mem::transmute::<i32, AVSampleFormat>(fmt),
+                )),
+            }
+        }
+    }
+
+    /// Get the layout of the channels in this frame
+    pub fn channel_layout(&self) -> ChannelLayout {
+        unsafe { ChannelLayout::from_bits_truncate((*self.frame).channel_layout) }
+    }
 }
 
 impl<AV> std::ops::Drop for Frame<AV> {
@@ -143,3 +373,22 @@ impl<'frame> Deref for Plane<'frame> {
         self.data
     }
 }
+
+pub struct PlaneMut<'frame> {
+    pub data: &'frame mut [u8],
+    pub linesize: usize,
+}
+
+impl<'frame> Deref for PlaneMut<'frame> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl<'frame> DerefMut for PlaneMut<'frame> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.data
+    }
+}
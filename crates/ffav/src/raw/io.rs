@@ -0,0 +1,303 @@
+//! Adapters which allow Rust I/O objects to be used as the source or destination
+//! of data for a Format
+
+use crate::error::{Error, Result};
+use ffav_sys::{
+    av_freep, av_malloc, avio_alloc_context, avio_context_free, avio_flush,
+    err::{av_err, AVERROR_EOF, AVERROR_EXTERNAL},
+    AVIOContext, AVSEEK_FORCE, AVSEEK_SIZE,
+};
+use std::{
+    any::TypeId,
+    ffi::c_void,
+    io::{self, Read, Seek, SeekFrom, Write},
+    os::raw::c_int,
+    panic::{self, AssertUnwindSafe},
+};
+
+/// Size of the buffer used to transfer data between libav and the I/O object
+const IO_BUFFER_SIZE: usize = 64 * 1024;
+
+// The `whence` values libav passes to the seek callback, these are the values
+// from `stdio.h` which are the same on all supported platforms
+const SEEK_SET: c_int = 0;
+const SEEK_CUR: c_int = 1;
+const SEEK_END: c_int = 2;
+
+type ReadCallback = Option<unsafe extern "C" fn(*mut c_void, *mut u8, c_int) -> c_int>;
+type WriteCallback = Option<unsafe extern "C" fn(*mut c_void, *mut u8, c_int) -> c_int>;
+type SeekCallback = Option<unsafe extern "C" fn(*mut c_void, i64, c_int) -> i64>;
+type FlushCallback = Option<unsafe fn(*mut c_void) -> c_int>;
+
+/// An `AVIOContext` which owns the Rust I/O object it transfers data with
+///
+/// The I/O object is boxed and passed to the libav callbacks as their `opaque`
+/// pointer, it is dropped along with this context.
+pub(crate) struct IoContext {
+    ctx: *mut AVIOContext,
+    opaque: *mut c_void,
+    opaque_type: TypeId,
+    free_opaque: unsafe fn(*mut c_void),
+    flush_opaque: FlushCallback,
+    seekable: bool,
+}
+
+impl IoContext {
+    /// Create a context which reads from a seekable source
+    pub(crate) fn reader<R: Read + Seek + Send + 'static>(reader: R) -> Result<IoContext> {
+        unsafe { Self::new(reader, Some(read_packet::<R>), None, Some(seek::<R>), None) }
+    }
+
+    /// Create a context which reads from a source that cannot seek
+    ///
+    /// Some input formats may not be readable without seeking.
+    pub(crate) fn nonseekable_reader<R: Read + Send + 'static>(reader: R) -> Result<IoContext> {
+        unsafe { Self::new(reader, Some(read_packet::<R>), None, None, None) }
+    }
+
+    /// Create a context which writes to a seekable destination
+    pub(crate) fn seekable_writer<W: Write + Seek + Send + 'static>(
+        writer: W,
+    ) -> Result<IoContext> {
+        unsafe {
+            Self::new(
+                writer,
+                None,
+                Some(write_packet::<W>),
+                Some(seek::<W>),
+                Some(flush_writer::<W>),
+            )
+        }
+    }
+
+    /// Create a context which writes to a destination that cannot seek
+    ///
+    /// Some output formats require seeking to finalize their output.
+    pub(crate) fn writer<W: Write + Send + 'static>(writer: W) -> Result<IoContext> {
+        unsafe {
+            Self::new(
+                writer,
+                None,
+                Some(write_packet::<W>),
+                None,
+                Some(flush_writer::<W>),
+            )
+        }
+    }
+
+    unsafe fn new<T: Send + 'static>(
+        inner: T,
+        read: ReadCallback,
+        write: WriteCallback,
+        seek: SeekCallback,
+        flush: FlushCallback,
+    ) -> Result<IoContext> {
+        let mut buffer = av_malloc(IO_BUFFER_SIZE as _) as *mut u8;
+
+        if buffer.is_null() {
+            return Err(Error::AllocationFailed("allocating I/O buffer"));
+        }
+
+        let opaque = Box::into_raw(Box::new(inner)) as *mut c_void;
+
+        let ctx = avio_alloc_context(
+            buffer,
+            IO_BUFFER_SIZE as c_int,
+            write.is_some() as c_int,
+            opaque,
+            read,
+            write,
+            seek,
+        );
+
+        if ctx.is_null() {
+            av_freep(&mut buffer as *mut *mut u8 as *mut c_void);
+            free_box::<T>(opaque);
+            return Err(Error::AllocationFailed("creating I/O context"));
+        }
+
+        Ok(IoContext {
+            ctx,
+            opaque,
+            opaque_type: TypeId::of::<T>(),
+            free_opaque: free_box::<T>,
+            flush_opaque: flush,
+            seekable: seek.is_some(),
+        })
+    }
+
+    /// Get the raw `AVIOContext` to attach to a Format
+    ///
+    /// # Safety
+    /// The pointer must not outlive this `IoContext`
+    pub(crate) unsafe fn as_raw(&self) -> *mut AVIOContext {
+        self.ctx
+    }
+
+    /// Check if the I/O object can seek
+    pub(crate) fn is_seekable(&self) -> bool {
+        self.seekable
+    }
+
+    /// Check if the wrapped I/O object is of type `T`
+    pub(crate) fn holds<T: 'static>(&self) -> bool {
+        self.opaque_type == TypeId::of::<T>()
+    }
+
+    /// Write out any buffered data, including data buffered by the I/O object
+    pub(crate) fn flush(&mut self) -> Result<()> {
+        unsafe {
+            avio_flush(self.ctx);
+
+            if let Some(flush) = self.flush_opaque {
+                let err = flush(self.opaque);
+
+                if err < 0 {
+                    return Err(Error::from_av_err("flushing output", err));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Destroy the context and return the wrapped I/O object if it is of type `T`
+    pub(crate) fn into_inner<T: 'static>(mut self) -> Option<T> {
+        if !self.holds::<T>() {
+            return None;
+        }
+
+        unsafe {
+            self.free_context();
+
+            let inner = Box::from_raw(self.opaque as *mut T);
+            // The I/O object is no longer ours to free
+            self.opaque = std::ptr::null_mut();
+
+            Some(*inner)
+        }
+    }
+
+    unsafe fn free_context(&mut self) {
+        if !self.ctx.is_null() {
+            // libav may have replaced the buffer we allocated, so free the
+            // one currently attached to the context
+            av_freep(&mut (*self.ctx).buffer as *mut *mut u8 as *mut c_void);
+            avio_context_free(&mut self.ctx);
+        }
+    }
+}
+
+impl std::ops::Drop for IoContext {
+    fn drop(&mut self) {
+        unsafe {
+            self.free_context();
+
+            if !self.opaque.is_null() {
+                (self.free_opaque)(self.opaque);
+                self.opaque = std::ptr::null_mut();
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for IoContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The wrapped I/O object is type erased so only its capabilities are shown
+        let writable = unsafe { !self.ctx.is_null() && (*self.ctx).write_flag != 0 };
+
+        f.debug_struct("IoContext")
+            .field("seekable", &self.seekable)
+            .field("writable", &writable)
+            .finish()
+    }
+}
+
+unsafe fn free_box<T>(ptr: *mut c_void) {
+    drop(Box::from_raw(ptr as *mut T));
+}
+
+/// Convert an I/O error into the closest libav error code
+fn io_error_to_av(err: &io::Error) -> c_int {
+    err.raw_os_error()
+        .map_or(av_err(ffav_sys::err::EIO), av_err)
+}
+
+unsafe extern "C" fn read_packet<R: Read>(
+    opaque: *mut c_void,
+    buf: *mut u8,
+    buf_size: c_int,
+) -> c_int {
+    let reader = &mut *(opaque as *mut R);
+    let buf = std::slice::from_raw_parts_mut(buf, buf_size as usize);
+
+    // Unwinding across the FFI boundary is undefined behaviour so any panic
+    // has to be caught and reported as an error instead
+    let result = panic::catch_unwind(AssertUnwindSafe(|| loop {
+        match reader.read(buf) {
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            res => return res,
+        }
+    }));
+
+    match result {
+        Ok(Ok(0)) => AVERROR_EOF,
+        Ok(Ok(read)) => read as c_int,
+        Ok(Err(e)) => io_error_to_av(&e),
+        Err(_) => AVERROR_EXTERNAL,
+    }
+}
+
+unsafe extern "C" fn write_packet<W: Write>(
+    opaque: *mut c_void,
+    buf: *mut u8,
+    buf_size: c_int,
+) -> c_int {
+    let writer = &mut *(opaque as *mut W);
+    let buf = std::slice::from_raw_parts(buf, buf_size as usize);
+
+    match panic::catch_unwind(AssertUnwindSafe(|| writer.write_all(buf))) {
+        Ok(Ok(())) => buf_size,
+        Ok(Err(e)) => io_error_to_av(&e),
+        Err(_) => AVERROR_EXTERNAL,
+    }
+}
+
+unsafe fn flush_writer<W: Write>(opaque: *mut c_void) -> c_int {
+    let writer = &mut *(opaque as *mut W);
+
+    match panic::catch_unwind(AssertUnwindSafe(|| writer.flush())) {
+        Ok(Ok(())) => 0,
+        Ok(Err(e)) => io_error_to_av(&e),
+        Err(_) => AVERROR_EXTERNAL,
+    }
+}
+
+unsafe extern "C" fn seek<S: Seek>(opaque: *mut c_void, offset: i64, whence: c_int) -> i64 {
+    let stream = &mut *(opaque as *mut S);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        // libav may ask for the total size of the stream instead of seeking
+        if whence & AVSEEK_SIZE != 0 {
+            let current = stream.stream_position()?;
+            let end = stream.seek(SeekFrom::End(0))?;
+            stream.seek(SeekFrom::Start(current))?;
+            return Ok(end);
+        }
+
+        let pos = match whence & !AVSEEK_FORCE {
+            SEEK_SET if offset >= 0 => SeekFrom::Start(offset as u64),
+            SEEK_CUR => SeekFrom::Current(offset),
+            SEEK_END => SeekFrom::End(offset),
+            _ => return Err(io::Error::from(io::ErrorKind::InvalidInput)),
+        };
+
+        stream.seek(pos)
+    }));
+
+    match result {
+        Ok(Ok(pos)) => pos as i64,
+        Ok(Err(e)) => io_error_to_av(&e) as i64,
+        Err(_) => AVERROR_EXTERNAL as i64,
+    }
+}
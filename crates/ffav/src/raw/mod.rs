@@ -10,5 +10,8 @@ pub mod codec;
 pub mod filter;
 pub mod format;
 pub mod frame;
+pub(crate) mod io;
 pub mod packet;
+pub mod resample;
+pub mod scale;
 pub mod stream;
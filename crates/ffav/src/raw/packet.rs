@@ -1,6 +1,7 @@
-use ffav_sys::{av_packet_alloc, av_packet_free, av_packet_unref, AVPacket};
+use ffav_sys::{av_packet_alloc, av_packet_free, av_packet_rescale_ts, av_packet_unref, AVPacket};
 
 use super::stream::Stream;
+use crate::util::time::{TimeBase, TimeBaseTicks};
 
 /// An encoded packet of data from a Format data contents are of an unknown
 /// type and must be passed to the proper Codec to be decoded into a frame.
@@ -51,6 +52,68 @@ impl Packet {
         }
     }
 
+    /// Get the presentation time of this packet in the TimeBase of its stream
+    ///
+    /// Will return None if the presentation time is unknown
+    pub fn pts(&self) -> Option<TimeBaseTicks> {
+        unsafe { TimeBaseTicks::from_av_timestamp((*self.pkt).pts) }
+    }
+
+    /// Set the presentation time of this packet in the TimeBase of its stream
+    pub fn set_pts(&mut self, pts: Option<TimeBaseTicks>) {
+        unsafe {
+            (*self.pkt).pts = TimeBaseTicks::option_as_av_timestamp(pts);
+        }
+    }
+
+    /// Get the decompression time of this packet in the TimeBase of its stream
+    ///
+    /// Will return None if the decompression time is unknown
+    pub fn dts(&self) -> Option<TimeBaseTicks> {
+        unsafe { TimeBaseTicks::from_av_timestamp((*self.pkt).dts) }
+    }
+
+    /// Set the decompression time of this packet in the TimeBase of its stream
+    pub fn set_dts(&mut self, dts: Option<TimeBaseTicks>) {
+        unsafe {
+            (*self.pkt).dts = TimeBaseTicks::option_as_av_timestamp(dts);
+        }
+    }
+
+    /// Get the duration of this packet in the TimeBase of its stream
+    ///
+    /// Will return None if the duration is unknown
+    pub fn duration(&self) -> Option<TimeBaseTicks> {
+        unsafe {
+            match (*self.pkt).duration {
+                // libav uses 0 for an unknown packet duration
+                0 => None,
+                duration => TimeBaseTicks::from_av_timestamp(duration),
+            }
+        }
+    }
+
+    /// Set the index of the stream this packet is intended to be added to
+    ///
+    /// This is required when writing packets to an output Format, where the
+    /// stream indices will typically differ from those of the input Format.
+    pub fn set_stream_index(&mut self, index: usize) {
+        unsafe {
+            (*self.pkt).stream_index = index as i32;
+        }
+    }
+
+    /// Convert the timestamps of this packet from one TimeBase to another
+    ///
+    /// Packets read from a Format are in the TimeBase of the stream they came
+    /// from, this must be used to convert them before writing them to a stream
+    /// with a different TimeBase.
+    pub fn rescale_ts(&mut self, from: TimeBase, to: TimeBase) {
+        unsafe {
+            av_packet_rescale_ts(self.pkt, from.as_av_rational(), to.as_av_rational());
+        }
+    }
+
     /// Chack if this packet belongs to the provided stream
     pub fn is_for_stream<AV>(&self, stream: &Stream<AV>) -> bool {
         stream.is_packet_for_stream(self)
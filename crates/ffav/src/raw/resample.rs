@@ -0,0 +1,344 @@
+//! Conversion of audio between sample formats, channel layouts and sample rates
+//!
+//! The `Resampler` is a thin wrapper around libswresample, it is useful for
+//! normalizing audio without the overhead of setting up a `FilterGraph`.
+
+use ffav_sys::{
+    err::{AVERROR_INPUT_CHANGED, AVERROR_OUTPUT_CHANGED},
+    swr_alloc_set_opts, swr_convert_frame, swr_free, swr_get_delay, swr_init, swr_next_pts,
+    swr_set_matrix, AVFrame, AVSampleFormat, SwrContext,
+};
+
+use crate::{
+    config::StreamConfig,
+    error::{Error, Result},
+    util::{
+        channels::ChannelLayout,
+        marker::Audio,
+        sampling::SampleFormat,
+        time::{Rounding, SampleRate, TimeBase, TimeBaseTicks},
+    },
+};
+
+use super::frame::Frame;
+
+/// Describes the layout of audio data on one side of a `Resampler`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioParameters {
+    /// The format of each sample
+    pub sample_format: SampleFormat,
+    /// The layout of the channels
+    pub channel_layout: ChannelLayout,
+    /// The number of samples per second
+    pub sample_rate: SampleRate,
+}
+
+impl AudioParameters {
+    /// Create a new set of audio parameters
+    pub fn new(
+        sample_format: SampleFormat,
+        channel_layout: ChannelLayout,
+        sample_rate: SampleRate,
+    ) -> Self {
+        AudioParameters {
+            sample_format,
+            channel_layout,
+            sample_rate,
+        }
+    }
+
+    /// Get the parameters of the frames described by a stream configuration
+    ///
+    /// This is typically the `out_stream_config()` of a decoder or the
+    /// `in_stream_config()` of an encoder.
+    pub fn from_stream_config(config: &StreamConfig<Audio>) -> Self {
+        AudioParameters {
+            sample_format: config.sample_format(),
+            channel_layout: config.channel_layout(),
+            sample_rate: config.sample_rate(),
+        }
+    }
+
+    fn num_channels(&self) -> usize {
+        self.channel_layout.bits().count_ones() as usize
+    }
+}
+
+/// Configuration for a `Resampler` which has not been initialized yet
+///
+/// Created by `Resampler::builder()`
+pub struct ResamplerBuilder {
+    input: AudioParameters,
+    output: AudioParameters,
+    input_time_base: Option<TimeBase>,
+    matrix: Option<Vec<f64>>,
+}
+
+impl ResamplerBuilder {
+    /// Set the TimeBase of the input frame timestamps
+    ///
+    /// This is typically the time base of the decoder producing the frames.
+    /// By default the input timestamps are assumed to count samples, in the
+    /// TimeBase of `1 / input sample rate`.
+    pub fn input_time_base(mut self, time_base: TimeBase) -> Self {
+        self.input_time_base = Some(time_base);
+        self
+    }
+
+    /// Use a custom matrix to mix the input channels into the output channels
+    ///
+    /// The matrix holds one row per output channel, each row holds the
+    /// coefficient applied to each input channel, in the channel order
+    /// described by `ChannelLayout`.
+    ///
+    /// By default libswresample chooses a matrix based on the channel layouts.
+    pub fn matrix(mut self, matrix: Vec<f64>) -> Self {
+        self.matrix = Some(matrix);
+        self
+    }
+
+    /// Initialize the Resampler
+    pub fn build(self) -> Result<Resampler> {
+        let ResamplerBuilder {
+            input,
+            output,
+            input_time_base,
+            matrix,
+        } = self;
+
+        if input.channel_layout.is_empty() || output.channel_layout.is_empty() {
+            return Err(Error::InvalidArguments(
+                "configuring resampler channel layouts",
+            ));
+        }
+
+        if input.sample_rate.as_hz() == 0 || output.sample_rate.as_hz() == 0 {
+            return Err(Error::InvalidArguments(
+                "configuring resampler sample rates",
+            ));
+        }
+
+        let mut resampler = unsafe {
+            let ctx = swr_alloc_set_opts(
+                std::ptr::null_mut(),
+                output.channel_layout.bits() as i64,
+                AVSampleFormat::from(output.sample_format),
+                output.sample_rate.as_hz() as i32,
+                input.channel_layout.bits() as i64,
+                AVSampleFormat::from(input.sample_format),
+                input.sample_rate.as_hz() as i32,
+                0,
+                std::ptr::null_mut(),
+            );
+
+            if ctx.is_null() {
+                return Err(Error::AllocationFailed("allocating resampler"));
+            }
+
+            // Take ownership now so the context is freed on the error paths
+            Resampler {
+                ctx,
+                input,
+                output,
+                input_time_base: input_time_base
+                    .unwrap_or_else(|| TimeBase::new(1, input.sample_rate.as_hz() as u64)),
+            }
+        };
+
+        if let Some(matrix) = matrix {
+            if matrix.len() != input.num_channels() * output.num_channels() {
+                return Err(Error::InvalidArguments("setting resampler mixing matrix"));
+            }
+
+            unsafe {
+                let err =
+                    swr_set_matrix(resampler.ctx, matrix.as_ptr(), input.num_channels() as i32);
+
+                if err < 0 {
+                    return Err(Error::from_av_err("setting resampler mixing matrix", err));
+                }
+            }
+        }
+
+        unsafe {
+            let err = swr_init(resampler.ctx);
+
+            if err < 0 {
+                return Err(Error::from_av_err("initializing resampler", err));
+            }
+        }
+
+        Ok(resampler)
+    }
+}
+
+/// Converts audio frames from one set of `AudioParameters` to another
+///
+/// Resampling introduces a delay, samples are buffered internally and only
+/// written out once enough input is available. At the end of the stream the
+/// remaining samples must be retrieved with `flush()`.
+pub struct Resampler {
+    ctx: *mut SwrContext,
+    input: AudioParameters,
+    output: AudioParameters,
+    input_time_base: TimeBase,
+}
+
+impl Resampler {
+    /// Start configuring a Resampler from the `input` to the `output` parameters
+    pub fn builder(input: AudioParameters, output: AudioParameters) -> ResamplerBuilder {
+        ResamplerBuilder {
+            input,
+            output,
+            input_time_base: None,
+            matrix: None,
+        }
+    }
+
+    /// Create a Resampler from the `input` to the `output` parameters using
+    /// the default settings
+    pub fn new(input: AudioParameters, output: AudioParameters) -> Result<Resampler> {
+        Self::builder(input, output).build()
+    }
+
+    /// Convert a frame of audio
+    ///
+    /// The converted samples are written into `output`, any existing data in
+    /// `output` is unreferenced. The output frame's timestamp is in the TimeBase
+    /// of `1 / output sample rate` and follows the timestamps of the input
+    /// frames, accounting for the samples buffered by the Resampler. If the
+    /// input frame has no timestamp the output continues from the previous
+    /// output frame.
+    ///
+    /// Returns `Error::SubmitMoreInput` if all of the input has been buffered
+    /// and no samples were produced.
+    pub fn convert(&mut self, input: &Frame<Audio>, output: &mut Frame<Audio>) -> Result<()> {
+        if input.sample_format() != Some(self.input.sample_format)
+            || input.channel_layout() != self.input.channel_layout
+            || input.sample_rate() != self.input.sample_rate
+        {
+            return Err(Error::FrameConfigMismatch);
+        }
+
+        unsafe { self.convert_raw(input.as_ptr(), output) }
+    }
+
+    /// Retrieve the samples which are still buffered at the end of the input
+    ///
+    /// This should be called repeatedly until it returns `Error::EoF`.
+    pub fn flush(&mut self, output: &mut Frame<Audio>) -> Result<()> {
+        // libswresample fails to allocate an empty output frame rather than
+        // signalling that everything has been flushed
+        if self.delay() == 0 {
+            output.unref();
+            return Err(Error::EoF);
+        }
+
+        match unsafe { self.convert_raw(std::ptr::null(), output) } {
+            Err(Error::SubmitMoreInput) => Err(Error::EoF),
+            res => res,
+        }
+    }
+
+    /// The number of output samples which are currently buffered
+    pub fn delay(&self) -> u64 {
+        unsafe { swr_get_delay(self.ctx, self.output.sample_rate.as_hz() as i64).max(0) as u64 }
+    }
+
+    /// Get the parameters of the accepted frames
+    pub fn input_parameters(&self) -> AudioParameters {
+        self.input
+    }
+
+    /// Get the parameters of the produced frames
+    pub fn output_parameters(&self) -> AudioParameters {
+        self.output
+    }
+
+    /// Get the raw `SwrContext` pointer
+    ///
+    /// # Safety
+    /// The pointer should not be held longer than the lifetime of the
+    /// `Resampler`. While holding the pointer it should be considered that
+    /// the `Resampler` is mutably borrowed.
+    pub unsafe fn as_raw(&mut self) -> *mut SwrContext {
+        self.ctx
+    }
+
+    /// # Safety
+    /// `input` must either be NULL to flush the resampler or a valid frame
+    /// matching the input parameters
+    unsafe fn convert_raw(
+        &mut self,
+        input: *const AVFrame,
+        output: &mut Frame<Audio>,
+    ) -> Result<()> {
+        output.unref();
+
+        // libswresample tracks timestamps in the TimeBase of
+        // `1 / (input sample rate * output sample rate)`
+        let swr_base = TimeBase::new(
+            1,
+            self.input.sample_rate.as_hz() as u64 * self.output.sample_rate.as_hz() as u64,
+        );
+
+        let in_pts = if input.is_null() {
+            None
+        } else {
+            TimeBaseTicks::from_av_timestamp((*input).pts).map(|pts| {
+                pts.to_timestamp(self.input_time_base)
+                    .with_new_timebase_rounded(swr_base, Rounding::Nearest)
+                    .get_ticks()
+            })
+        };
+
+        // This must be called before converting so the samples that are
+        // already buffered are accounted for. An unknown input timestamp
+        // returns the timestamp following the previous output.
+        let out_pts = swr_next_pts(self.ctx, TimeBaseTicks::option_as_av_timestamp(in_pts));
+
+        let out = output.as_raw();
+        (*out).format = AVSampleFormat::from(self.output.sample_format) as i32;
+        (*out).channel_layout = self.output.channel_layout.bits();
+        (*out).channels = self.output.num_channels() as i32;
+        (*out).sample_rate = self.output.sample_rate.as_hz() as i32;
+
+        // The output frame buffers are allocated by `swr_convert_frame()`
+        let err = swr_convert_frame(self.ctx, out, input);
+
+        if err == AVERROR_INPUT_CHANGED || err == AVERROR_OUTPUT_CHANGED {
+            return Err(Error::FrameConfigMismatch);
+        } else if err < 0 {
+            return Err(Error::from_av_err("resampling audio frame", err));
+        }
+
+        if output.num_samples() == 0 {
+            output.unref();
+            return Err(Error::SubmitMoreInput);
+        }
+
+        let out_pts = TimeBaseTicks::new(out_pts)
+            .to_timestamp(swr_base)
+            .with_new_timebase_rounded(
+                TimeBase::new(1, self.output.sample_rate.as_hz() as u64),
+                Rounding::Nearest,
+            );
+        output.set_pts(Some(out_pts.get_ticks()));
+
+        Ok(())
+    }
+}
+
+impl std::ops::Drop for Resampler {
+    fn drop(&mut self) {
+        unsafe {
+            // `swr_free` NULLs the pointer
+            swr_free(&mut self.ctx);
+        }
+    }
+}
+
+// SAFETY: The Resampler is the sole owner of the contained context pointer and
+// has no interior mutability.
+unsafe impl std::marker::Send for Resampler {}
+unsafe impl std::marker::Sync for Resampler {}
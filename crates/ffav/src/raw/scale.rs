@@ -0,0 +1,372 @@
+//! Conversion of video frames between pixel formats and sizes
+//!
+//! The `Scaler` is a thin wrapper around libswscale, it is useful for
+//! converting frames without the overhead of setting up a `FilterGraph`.
+
+use ffav_sys::{
+    av_frame_copy_props, av_frame_get_buffer, sws_freeContext, sws_getCachedContext,
+    sws_getCoefficients, sws_getColorspaceDetails, sws_scale, sws_setColorspaceDetails,
+    AVPixelFormat, SwsContext, SWS_AREA, SWS_BICUBIC, SWS_BILINEAR, SWS_CS_BT2020, SWS_CS_DEFAULT,
+    SWS_CS_FCC, SWS_CS_ITU601, SWS_CS_ITU709, SWS_CS_SMPTE240M, SWS_FAST_BILINEAR, SWS_GAUSS,
+    SWS_LANCZOS, SWS_POINT, SWS_SINC, SWS_SPLINE,
+};
+
+use crate::{
+    error::{Error, Result},
+    util::{
+        color::{ColorRange, ColorSpace, PixelFormat},
+        marker::Video,
+    },
+};
+
+use super::frame::Frame;
+
+/// The interpolation algorithm used when resizing frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScalingAlgorithm {
+    /// Fast, lower quality, bilinear scaling
+    FastBilinear,
+    /// Bilinear scaling
+    Bilinear,
+    /// Bicubic scaling
+    Bicubic,
+    /// Nearest neighbour scaling
+    Nearest,
+    /// Averaging of the covered area
+    Area,
+    /// Gaussian scaling
+    Gauss,
+    /// Sinc scaling
+    Sinc,
+    /// Lanczos scaling
+    Lanczos,
+    /// Natural bicubic spline scaling
+    Spline,
+}
+
+impl ScalingAlgorithm {
+    fn flags(self) -> i32 {
+        match self {
+            ScalingAlgorithm::FastBilinear => SWS_FAST_BILINEAR,
+            ScalingAlgorithm::Bilinear => SWS_BILINEAR,
+            ScalingAlgorithm::Bicubic => SWS_BICUBIC,
+            ScalingAlgorithm::Nearest => SWS_POINT,
+            ScalingAlgorithm::Area => SWS_AREA,
+            ScalingAlgorithm::Gauss => SWS_GAUSS,
+            ScalingAlgorithm::Sinc => SWS_SINC,
+            ScalingAlgorithm::Lanczos => SWS_LANCZOS,
+            ScalingAlgorithm::Spline => SWS_SPLINE,
+        }
+    }
+
+    /// The name of the algorithm in the `flags` option of the `scale` filter
+    pub(crate) fn option_name(self) -> &'static str {
+        match self {
+            ScalingAlgorithm::FastBilinear => "fast_bilinear",
+            ScalingAlgorithm::Bilinear => "bilinear",
+            ScalingAlgorithm::Bicubic => "bicubic",
+            ScalingAlgorithm::Nearest => "neighbor",
+            ScalingAlgorithm::Area => "area",
+            ScalingAlgorithm::Gauss => "gauss",
+            ScalingAlgorithm::Sinc => "sinc",
+            ScalingAlgorithm::Lanczos => "lanczos",
+            ScalingAlgorithm::Spline => "spline",
+        }
+    }
+}
+
+impl Default for ScalingAlgorithm {
+    /// Bicubic, the same default as the `scale` filter
+    fn default() -> Self {
+        ScalingAlgorithm::Bicubic
+    }
+}
+
+/// Converts video frames to a target pixel format and size
+///
+/// The underlying scaling context is reused between frames and only recreated
+/// when the format or size of the input frames changes.
+pub struct Scaler {
+    ctx: *mut SwsContext,
+    // The width, height and format of the input the context was created for
+    ctx_input: Option<(u32, u32, PixelFormat)>,
+    // The input color space and range the color conversion was configured
+    // for, along with the resulting output color space and range
+    ctx_colors: Option<((ColorSpace, ColorRange), (ColorSpace, ColorRange))>,
+    format: PixelFormat,
+    size: Option<(u32, u32)>,
+    algorithm: ScalingAlgorithm,
+    color_space: Option<ColorSpace>,
+    color_range: Option<ColorRange>,
+}
+
+impl Scaler {
+    /// Create a Scaler converting frames to `format`
+    ///
+    /// By default the size, color space and color range of the input frames
+    /// are kept where the output pixel format allows it.
+    pub fn new(format: PixelFormat) -> Scaler {
+        Scaler {
+            ctx: std::ptr::null_mut(),
+            ctx_input: None,
+            ctx_colors: None,
+            format,
+            size: None,
+            algorithm: ScalingAlgorithm::default(),
+            color_space: None,
+            color_range: None,
+        }
+    }
+
+    /// Resize frames to `width` x `height`
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    /// Set the algorithm used to resize frames
+    pub fn algorithm(mut self, algorithm: ScalingAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Set the color space used when converting to a YUV pixel format
+    ///
+    /// This is ignored for RGB pixel formats, which always use the RGB color
+    /// space.
+    pub fn color_space(mut self, space: ColorSpace) -> Self {
+        self.color_space = Some(space);
+        self
+    }
+
+    /// Set the color range of the output frames
+    ///
+    /// By default the range of the input frames is kept, except for RGB pixel
+    /// formats which use the full range.
+    pub fn color_range(mut self, range: ColorRange) -> Self {
+        self.color_range = Some(range);
+        self
+    }
+
+    /// Get the pixel format of the produced frames
+    pub fn pixel_format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// Convert a frame
+    ///
+    /// The converted image is written into `output`, any existing data in
+    /// `output` is unreferenced. The timestamps and other properties of the
+    /// input frame are copied to the output.
+    pub fn scale(&mut self, input: &Frame<Video>, output: &mut Frame<Video>) -> Result<()> {
+        let in_format = input.pixel_format();
+        if in_format == PixelFormat::NONE {
+            return Err(Error::InvalidArguments("scaling an empty frame"));
+        }
+
+        let (in_width, in_height) = (input.width(), input.height());
+        let (out_width, out_height) = self.size.unwrap_or((in_width, in_height));
+
+        unsafe {
+            let previous_ctx = self.ctx;
+
+            // The previous context is freed if it can't be reused
+            self.ctx = sws_getCachedContext(
+                self.ctx,
+                in_width as i32,
+                in_height as i32,
+                AVPixelFormat::from(in_format),
+                out_width as i32,
+                out_height as i32,
+                AVPixelFormat::from(self.format),
+                self.algorithm.flags(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null(),
+            );
+
+            if self.ctx.is_null() {
+                self.ctx_input = None;
+                self.ctx_colors = None;
+                return Err(Error::InvalidArguments("creating scaling context"));
+            }
+
+            // A new context starts with the default color details. The input
+            // is compared too as a new context may be allocated at the address
+            // of the one it replaced.
+            let in_config = (in_width, in_height, in_format);
+            if self.ctx != previous_ctx || self.ctx_input != Some(in_config) {
+                self.ctx_input = Some(in_config);
+                self.ctx_colors = None;
+            }
+
+            let in_colors = (input.color_space(), input.color_range());
+            let (out_space, out_range) = match self.ctx_colors {
+                Some((configured, out_colors)) if configured == in_colors => out_colors,
+                _ => {
+                    let out_colors = self.set_colorspace_details(in_colors);
+                    self.ctx_colors = Some((in_colors, out_colors));
+                    out_colors
+                }
+            };
+
+            output.unref();
+
+            let out = output.as_raw();
+            (*out).width = out_width as i32;
+            (*out).height = out_height as i32;
+            (*out).format = AVPixelFormat::from(self.format) as i32;
+
+            let err = av_frame_get_buffer(out, 0);
+            if err < 0 {
+                return Err(Error::from_av_err("allocating scaled frame", err));
+            }
+
+            let err = av_frame_copy_props(out, input.as_ptr());
+            if err < 0 {
+                return Err(Error::from_av_err("copying frame properties", err));
+            }
+
+            let inp = input.as_ptr();
+            let err = sws_scale(
+                self.ctx,
+                (*inp).data.as_ptr() as *const *const u8,
+                (*inp).linesize.as_ptr(),
+                0,
+                in_height as i32,
+                (*out).data.as_ptr(),
+                (*out).linesize.as_ptr(),
+            );
+
+            if err < 0 {
+                return Err(Error::from_av_err("scaling frame", err));
+            }
+
+            output.set_color_space(out_space);
+            output.set_color_range(out_range);
+        }
+
+        Ok(())
+    }
+
+    /// Get the raw `SwsContext` pointer
+    ///
+    /// The pointer will be NULL until the first frame has been scaled.
+    ///
+    /// # Safety
+    /// The pointer should not be held longer than the lifetime of the
+    /// `Scaler`, or across calls to `scale()` which may replace the context.
+    /// Changes to the color details of the context may be overwritten when
+    /// the color properties of the input frames change.
+    pub unsafe fn as_raw(&mut self) -> *mut SwsContext {
+        self.ctx
+    }
+
+    /// Configure the color conversion from input frames with the color space
+    /// and range `in_colors` to the output frames
+    ///
+    /// Values which aren't known are left at the libswscale defaults.
+    /// Returns the color space and range of the output frames.
+    ///
+    /// # Safety
+    /// The scaling context must have been created
+    unsafe fn set_colorspace_details(
+        &mut self,
+        (in_space, in_range): (ColorSpace, ColorRange),
+    ) -> (ColorSpace, ColorRange) {
+        // RGB data isn't encoded with a YUV matrix, so it must not be tagged
+        // with the color space of a YUV input
+        let out_space = if self.format.is_rgb() {
+            ColorSpace::RGB
+        } else {
+            self.color_space.unwrap_or(in_space)
+        };
+        // RGB data is full range unless a range was requested
+        let out_range = match self.color_range {
+            Some(range) => range,
+            None if self.format.is_rgb() => ColorRange::Full,
+            None => in_range,
+        };
+
+        let mut inv_table = std::ptr::null_mut();
+        let mut src_range = 0;
+        let mut table = std::ptr::null_mut();
+        let mut dst_range = 0;
+        let mut brightness = 0;
+        let mut contrast = 0;
+        let mut saturation = 0;
+
+        // This fails for formats where the details don't apply, in which case
+        // there is nothing to configure
+        if sws_getColorspaceDetails(
+            self.ctx,
+            &mut inv_table,
+            &mut src_range,
+            &mut table,
+            &mut dst_range,
+            &mut brightness,
+            &mut contrast,
+            &mut saturation,
+        ) < 0
+        {
+            return (out_space, out_range);
+        }
+
+        let mut inv_table = inv_table as *const i32;
+        if in_space != ColorSpace::Unspecified {
+            inv_table = sws_getCoefficients(sws_coefficients(in_space));
+        }
+
+        let mut table = table as *const i32;
+        if out_space != ColorSpace::Unspecified && out_space != ColorSpace::RGB {
+            table = sws_getCoefficients(sws_coefficients(out_space));
+        }
+
+        match in_range {
+            ColorRange::Full => src_range = 1,
+            ColorRange::Limited => src_range = 0,
+            ColorRange::Unspecified => {}
+        }
+
+        match out_range {
+            ColorRange::Full => dst_range = 1,
+            ColorRange::Limited => dst_range = 0,
+            ColorRange::Unspecified => {}
+        }
+
+        // NOTE: This returns an error when neither side is YUV, the details
+        // don't matter for those conversions so it is ignored
+        sws_setColorspaceDetails(
+            self.ctx, inv_table, src_range, table, dst_range, brightness, contrast, saturation,
+        );
+
+        (out_space, out_range)
+    }
+}
+
+/// Map a color space to the coefficient table used by libswscale
+fn sws_coefficients(space: ColorSpace) -> i32 {
+    match space {
+        ColorSpace::BT709 => SWS_CS_ITU709,
+        ColorSpace::FCC => SWS_CS_FCC,
+        ColorSpace::BT470BG | ColorSpace::SMPTE170M => SWS_CS_ITU601,
+        ColorSpace::SMPTE240M => SWS_CS_SMPTE240M,
+        ColorSpace::BT2020NCL | ColorSpace::BT2020CL => SWS_CS_BT2020,
+        _ => SWS_CS_DEFAULT,
+    }
+}
+
+impl std::ops::Drop for Scaler {
+    fn drop(&mut self) {
+        unsafe {
+            // `sws_freeContext` does nothing when passed NULL
+            sws_freeContext(self.ctx);
+            self.ctx = std::ptr::null_mut();
+        }
+    }
+}
+
+// SAFETY: The Scaler is the sole owner of the contained context pointer and
+// has no interior mutability.
+unsafe impl std::marker::Send for Scaler {}
+unsafe impl std::marker::Sync for Scaler {}
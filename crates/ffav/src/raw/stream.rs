@@ -3,7 +3,7 @@ use std::marker::PhantomData;
 
 use crate::{
     config::StreamConfig,
-    util::{marker::Unknown, MediaType},
+    util::{marker::Unknown, time::TimeBase, MediaType},
 };
 
 use super::packet::Packet;
@@ -35,6 +35,20 @@ impl<'ctx, AV> Stream<'ctx, AV> {
         self.stream
     }
 
+    /// Get the type of media contained in this stream
+    pub fn media_type(&self) -> AVMediaType {
+        unsafe { (*(*self.stream).codecpar).codec_type }
+    }
+
+    /// Get the TimeBase of the timestamps of packets in this stream
+    pub fn time_base(&self) -> TimeBase {
+        // Streams are given a time base when they are created, so this only
+        // falls back if it was explicitly cleared
+        unsafe {
+            TimeBase::from_av_rational(&(*self.stream).time_base).unwrap_or(TimeBase::NEW_STREAM)
+        }
+    }
+
     /// Check if the provided packet belongs to this data stream
     pub fn is_packet_for_stream(&self, packet: &Packet) -> bool {
         unsafe { (*self.stream).index == (*packet.as_raw()).stream_index }
@@ -0,0 +1,6 @@
+//! Helper functions and structures to copy A/V streams between Formats
+//! without decoding them
+
+mod remuxer;
+
+pub use remuxer::Remuxer;
@@ -0,0 +1,213 @@
+use std::path::Path;
+
+use crate::{
+    config::Dictionary,
+    error::{Error, Result},
+    raw::{format::Format, packet::Packet, stream::Stream},
+    util::{
+        marker::{Input, Output, Unknown},
+        time::TimeBase,
+    },
+};
+
+/// Where packets of a selected input stream are written in the output
+#[derive(Debug, Clone, Copy)]
+struct StreamMapping {
+    out_index: usize,
+    in_time_base: TimeBase,
+    out_time_base: TimeBase,
+}
+
+/// Copy selected streams from an input Format to an output Format without
+/// re-encoding them
+///
+/// The codec parameters of each selected stream are copied to the output and
+/// packet timestamps are converted between the TimeBases of the streams. The
+/// codecs of the selected streams must be supported by the output container.
+pub struct Remuxer {
+    input: Format<Input>,
+    output: Format<Output>,
+    // Indexed by the input stream index, `None` for streams which aren't copied
+    mapping: Vec<Option<StreamMapping>>,
+    packet: Packet,
+}
+
+impl Remuxer {
+    /// Open the `input` file and remux its audio and video streams into the
+    /// `output` file
+    ///
+    /// The output format type will be determined by the output file extension
+    pub fn open<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q) -> Result<Remuxer> {
+        Self::open_with_streams(input, output, default_stream_selector)
+    }
+
+    /// Open the `input` file and remux the selected streams into the `output` file
+    ///
+    /// The provided function `stream_selector` is called for every stream of the
+    /// input and should return `true` for the streams to be copied.
+    pub fn open_with_streams<P: AsRef<Path>, Q: AsRef<Path>, S>(
+        input: P,
+        output: Q,
+        stream_selector: S,
+    ) -> Result<Remuxer>
+    where
+        S: FnMut(&Stream<Unknown>) -> bool,
+    {
+        Self::from_formats_with_streams(
+            Format::open_input(input)?,
+            Format::open_output(output)?,
+            stream_selector,
+        )
+    }
+
+    /// Set up existing Formats for remuxing their audio and video streams
+    ///
+    /// No streams may have been added to the output Format.
+    pub fn from_formats(input: Format<Input>, output: Format<Output>) -> Result<Remuxer> {
+        Self::from_formats_with_streams(input, output, default_stream_selector)
+    }
+
+    /// Set up existing Formats for remuxing the selected streams
+    ///
+    /// No streams may have been added to the output Format.
+    pub fn from_formats_with_streams<S>(
+        input: Format<Input>,
+        output: Format<Output>,
+        stream_selector: S,
+    ) -> Result<Remuxer>
+    where
+        S: FnMut(&Stream<Unknown>) -> bool,
+    {
+        Self::from_formats_with_options(input, output, stream_selector, &mut Dictionary::new())
+    }
+
+    /// Set up existing Formats for remuxing the selected streams providing
+    /// options to the output muxer
+    ///
+    /// On return the `options` dictionary will contain only the options which
+    /// were not recognized by the muxer.
+    pub fn from_formats_with_options<S>(
+        input: Format<Input>,
+        mut output: Format<Output>,
+        mut stream_selector: S,
+        options: &mut Dictionary,
+    ) -> Result<Remuxer>
+    where
+        S: FnMut(&Stream<Unknown>) -> bool,
+    {
+        if output.get_configuration().num_streams() != 0 {
+            return Err(Error::InvalidArguments("setting up remuxer output"));
+        }
+
+        let mut selected = Vec::with_capacity(input.streams().len());
+        for stream in input.streams() {
+            if stream_selector(stream) {
+                selected.push(Some(output.add_stream_from(stream)?));
+            } else {
+                selected.push(None);
+            }
+        }
+
+        if selected.iter().all(Option::is_none) {
+            return Err(Error::StreamNotFound);
+        }
+
+        output.write_header_with_options(options)?;
+
+        // The muxer may have chosen different TimeBases when writing the header
+        let mapping = selected
+            .into_iter()
+            .zip(input.streams())
+            .map(|(out_index, in_stream)| {
+                out_index.map(|out_index| StreamMapping {
+                    out_index,
+                    in_time_base: in_stream.time_base(),
+                    out_time_base: output.streams()[out_index].time_base(),
+                })
+            })
+            .collect();
+
+        Ok(Remuxer {
+            input,
+            output,
+            mapping,
+            packet: Packet::new(),
+        })
+    }
+
+    /// Copy the next packet of a selected stream to the output
+    ///
+    /// Returns `Error::EoF` once the end of the input has been reached.
+    pub fn remux_next_packet(&mut self) -> Result<()> {
+        loop {
+            self.input.get_next_packet_into(&mut self.packet)?;
+
+            let mapping = match self.mapping.get(self.packet.stream_index()) {
+                Some(Some(mapping)) => *mapping,
+                // Skip packets from streams that aren't being copied
+                _ => continue,
+            };
+
+            self.packet
+                .rescale_ts(mapping.in_time_base, mapping.out_time_base);
+            self.packet.set_stream_index(mapping.out_index);
+
+            unsafe {
+                // The byte position in the input is meaningless in the output
+                (*self.packet.as_raw()).pos = -1;
+            }
+
+            return self.output.write_packet(&mut self.packet);
+        }
+    }
+
+    /// Copy all remaining packets of the selected streams to the output
+    pub fn run(&mut self) -> Result<()> {
+        loop {
+            match self.remux_next_packet() {
+                Ok(()) => {}
+                Err(Error::EoF) => return Ok(()),
+                Err(x) => return Err(x),
+            }
+        }
+    }
+
+    /// Copy all remaining packets and finalize the output
+    pub fn finish(mut self) -> Result<()> {
+        self.run()?;
+        self.output.finish()
+    }
+
+    /// Copy all remaining packets, finalize the output and return the writer
+    /// the output Format was opened with
+    ///
+    /// See `Format::finish_into_writer()` for details.
+    pub fn finish_into_writer<W: 'static>(mut self) -> Result<W> {
+        self.run()?;
+        self.output.finish_into_writer()
+    }
+
+    /// Get the index of the output stream which the input stream with index
+    /// `input_index` is copied to, `None` if the stream isn't copied
+    pub fn output_stream_index(&self, input_index: usize) -> Option<usize> {
+        self.mapping
+            .get(input_index)
+            .copied()
+            .flatten()
+            .map(|mapping| mapping.out_index)
+    }
+
+    /// Get the input Format
+    pub fn input(&self) -> &Format<Input> {
+        &self.input
+    }
+
+    /// Get the output Format
+    pub fn output(&self) -> &Format<Output> {
+        &self.output
+    }
+}
+
+fn default_stream_selector(stream: &Stream<Unknown>) -> bool {
+    stream.is_audio() || stream.is_video()
+}
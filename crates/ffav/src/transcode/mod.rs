@@ -0,0 +1,7 @@
+//! Helper functions and structures to convert A/V streams between codecs and
+//! Formats
+
+mod pipeline;
+mod transcoder;
+
+pub use transcoder::{Transcoder, TranscoderBuilder};
@@ -0,0 +1,341 @@
+use crate::{
+    error::{Error, Result},
+    raw::{
+        codec::{Codec, EncoderBuilder},
+        filter::{
+            audio::{ABufferSink, ABufferSource, AFormat},
+            video::{VBufferSink, VBufferSource, VFormat, VScale},
+            Filter, FilterGraph, FilterInput, FilterOutput, InputHandle, OutputHandle,
+        },
+        format::Format,
+        frame::Frame,
+        packet::Packet,
+        stream::Stream,
+    },
+    util::{
+        marker::{Audio, Configured, Decode, Encode, Output, Video},
+        time::{Rounding, TimeBase, TimeBaseTicks},
+        MediaType,
+    },
+};
+use ffav_sys::AVPictureType;
+
+/// How the packets of a single input stream are handled
+pub(super) enum StreamPipeline {
+    Copy(CopiedStream),
+    Video(Box<Pipeline<Video, VBufferSource, VBufferSink>>),
+    Audio(Box<Pipeline<Audio, ABufferSource, ABufferSink>>),
+}
+
+impl StreamPipeline {
+    /// Process a packet read from the input stream, writing any produced
+    /// packets to the `output`
+    pub(super) fn submit_packet(
+        &mut self,
+        packet: &mut Packet,
+        output: &mut Format<Output>,
+    ) -> Result<()> {
+        match self {
+            StreamPipeline::Copy(copy) => copy.submit_packet(packet, output),
+            StreamPipeline::Video(pipeline) => pipeline.submit_packet(packet, output),
+            StreamPipeline::Audio(pipeline) => pipeline.submit_packet(packet, output),
+        }
+    }
+
+    /// Write out all data buffered in the pipeline at the end of the input
+    pub(super) fn finish(&mut self, output: &mut Format<Output>) -> Result<()> {
+        match self {
+            StreamPipeline::Copy(_) => Ok(()),
+            StreamPipeline::Video(pipeline) => pipeline.finish(output),
+            StreamPipeline::Audio(pipeline) => pipeline.finish(output),
+        }
+    }
+
+    /// The index of the stream written to in the output Format
+    pub(super) fn output_index(&self) -> usize {
+        match self {
+            StreamPipeline::Copy(copy) => copy.out_index,
+            StreamPipeline::Video(pipeline) => pipeline.out_index,
+            StreamPipeline::Audio(pipeline) => pipeline.out_index,
+        }
+    }
+
+    /// Update the TimeBase of the output stream once the header has been written
+    pub(super) fn set_output_time_base(&mut self, time_base: TimeBase) {
+        match self {
+            StreamPipeline::Copy(copy) => copy.out_time_base = time_base,
+            StreamPipeline::Video(pipeline) => pipeline.out_time_base = time_base,
+            StreamPipeline::Audio(pipeline) => pipeline.out_time_base = time_base,
+        }
+    }
+}
+
+/// A stream which is copied to the output without re-encoding
+pub(super) struct CopiedStream {
+    out_index: usize,
+    in_time_base: TimeBase,
+    out_time_base: TimeBase,
+}
+
+impl CopiedStream {
+    pub(super) fn new<AV>(stream: &Stream<'_, AV>, output: &mut Format<Output>) -> Result<Self> {
+        Ok(CopiedStream {
+            out_index: output.add_stream_from(stream)?,
+            in_time_base: stream.time_base(),
+            out_time_base: stream.time_base(),
+        })
+    }
+
+    fn submit_packet(&mut self, packet: &mut Packet, output: &mut Format<Output>) -> Result<()> {
+        packet.rescale_ts(self.in_time_base, self.out_time_base);
+        packet.set_stream_index(self.out_index);
+
+        unsafe {
+            // The byte position in the input is meaningless in the output
+            (*packet.as_raw()).pos = -1;
+        }
+
+        output.write_packet(packet)
+    }
+}
+
+/// A stream which is decoded, converted by a FilterGraph to the format the
+/// encoder accepts and then re-encoded
+pub(super) struct Pipeline<AV, I, O> {
+    decoder: Codec<Decode, AV>,
+    graph: FilterGraph<Configured>,
+    source: InputHandle<I>,
+    sink: OutputHandle<O>,
+    encoder: Codec<Encode, AV>,
+    out_index: usize,
+    sink_time_base: TimeBase,
+    encoder_time_base: TimeBase,
+    out_time_base: TimeBase,
+    // The end time of the latest decoded frame in the FilterGraph input
+    input_end: Option<TimeBaseTicks>,
+    decoded: Frame<AV>,
+    filtered: Frame<AV>,
+    encoded: Packet,
+}
+
+impl Pipeline<Video, VBufferSource, VBufferSink> {
+    pub(super) fn new_video(
+        stream: &Stream<'_, Video>,
+        encoder: EncoderBuilder<Video>,
+        output: &mut Format<Output>,
+    ) -> Result<Self> {
+        let decoder = Codec::open_decode(stream)?;
+
+        let encoder = encoder.defaults_from_decoder(&decoder)?;
+        let (width, height, pixel_format) = encoder.frame_config();
+
+        let mut graph = FilterGraph::new()?;
+
+        let source = graph.add_input(VBufferSource::from_decoded_stream(
+            "in",
+            decoder.out_stream_config(),
+        ))?;
+        let scale = graph.add(VScale::new("scale", width, height))?;
+        let format = graph.add(VFormat::new("format", Some(pixel_format)))?;
+        let sink = graph.add_output(VBufferSink::new("out"))?;
+
+        graph.connect(&source, 0, &scale, 0)?;
+        graph.connect(&scale, 0, &format, 0)?;
+        graph.connect(&format, 0, &sink, 0)?;
+
+        let graph = graph.configure()?;
+
+        // The encoder takes the frames exactly as the FilterGraph produces
+        // them, keeping a time base if the user chose one
+        let time_base = encoder.configured_time_base();
+        let mut encoder = encoder.filter_output(&graph.output_config(&sink)?);
+        if let Some(time_base) = time_base {
+            encoder = encoder.time_base(time_base);
+        }
+        if output.needs_global_header() {
+            encoder = encoder.global_header(true);
+        }
+        let encoder = encoder.open()?;
+
+        Self::from_parts(decoder, graph, source, sink, encoder, output)
+    }
+}
+
+impl Pipeline<Audio, ABufferSource, ABufferSink> {
+    pub(super) fn new_audio(
+        stream: &Stream<'_, Audio>,
+        encoder: EncoderBuilder<Audio>,
+        output: &mut Format<Output>,
+    ) -> Result<Self> {
+        let decoder = Codec::open_decode(stream)?;
+
+        let encoder = encoder.defaults_from_decoder(&decoder)?;
+        let (sample_rate, sample_format, channel_layout) = encoder.sample_config();
+
+        let mut graph = FilterGraph::new()?;
+
+        let source = graph.add_input(ABufferSource::from_decoded_stream(
+            "in",
+            decoder.out_stream_config(),
+        ))?;
+        let format = graph.add(AFormat::new(
+            "format",
+            Some(sample_rate.as_hz()),
+            Some(sample_format),
+            Some(channel_layout),
+        ))?;
+        let sink = graph.add_output(ABufferSink::new("out"))?;
+
+        graph.connect(&source, 0, &format, 0)?;
+        graph.connect(&format, 0, &sink, 0)?;
+
+        let mut graph = graph.configure()?;
+
+        // As for video, the encoder is configured from the FilterGraph output
+        let time_base = encoder.configured_time_base();
+        let mut encoder = encoder.filter_output(&graph.output_config(&sink)?);
+        if let Some(time_base) = time_base {
+            encoder = encoder.time_base(time_base);
+        }
+        if output.needs_global_header() {
+            encoder = encoder.global_header(true);
+        }
+        let encoder = encoder.open()?;
+
+        // Encoders with a fixed frame size need the samples regrouped
+        if let Some(frame_size) = encoder.frame_size() {
+            graph.set_output_frame_size(&sink, frame_size)?;
+        }
+
+        Self::from_parts(decoder, graph, source, sink, encoder, output)
+    }
+}
+
+impl<AV, I, O> Pipeline<AV, I, O>
+where
+    AV: MediaType,
+    I: Filter + FilterInput<StreamType = AV>,
+    O: Filter + FilterOutput<StreamType = AV>,
+{
+    fn from_parts(
+        decoder: Codec<Decode, AV>,
+        graph: FilterGraph<Configured>,
+        source: InputHandle<I>,
+        sink: OutputHandle<O>,
+        encoder: Codec<Encode, AV>,
+        output: &mut Format<Output>,
+    ) -> Result<Self> {
+        let sink_time_base = graph.output_config(&sink)?.time_base();
+        let encoder_time_base = encoder.in_stream_config().time_base();
+        let out_index = output.add_stream_for_encoder(&encoder)?;
+
+        Ok(Pipeline {
+            decoder,
+            graph,
+            source,
+            sink,
+            encoder,
+            out_index,
+            sink_time_base,
+            encoder_time_base,
+            // This is replaced once the header has been written
+            out_time_base: encoder_time_base,
+            input_end: None,
+            decoded: Frame::new(),
+            filtered: Frame::new(),
+            encoded: Packet::new(),
+        })
+    }
+
+    fn submit_packet(&mut self, packet: &mut Packet, output: &mut Format<Output>) -> Result<()> {
+        self.decoder.submit_packet(packet)?;
+        self.drain_decoder(output)
+    }
+
+    fn finish(&mut self, output: &mut Format<Output>) -> Result<()> {
+        self.decoder.send_eof()?;
+        self.drain_decoder(output)
+    }
+
+    /// Pass all available decoded frames through the rest of the pipeline
+    ///
+    /// Once the decoder has been fully drained the end of the stream is
+    /// propagated through the FilterGraph and encoder.
+    fn drain_decoder(&mut self, output: &mut Format<Output>) -> Result<()> {
+        loop {
+            match self.decoder.get_next_frame_into(&mut self.decoded) {
+                Ok(()) => {}
+                Err(Error::SubmitMoreInput) => return Ok(()),
+                Err(Error::EoF) => return self.finish_graph(output),
+                Err(x) => return Err(x),
+            }
+
+            let pts = self.decoded.get_best_effort_timestamp();
+            self.decoded.set_pts(pts);
+
+            if let Some(pts) = pts {
+                let duration = self
+                    .decoded
+                    .get_duration()
+                    .map_or(0, TimeBaseTicks::as_av_timestamp);
+                self.input_end = Some(TimeBaseTicks::new(
+                    pts.as_av_timestamp().saturating_add(duration),
+                ));
+            }
+
+            self.graph.submit_input(&self.source, &mut self.decoded)?;
+            self.drain_graph(output)?;
+        }
+    }
+
+    fn finish_graph(&mut self, output: &mut Format<Output>) -> Result<()> {
+        // The end time lets filters such as `fps` output their last frames
+        // with the correct duration
+        self.graph.close_input(&self.source, self.input_end)?;
+        self.drain_graph(output)?;
+
+        self.encoder.send_eof()?;
+        self.drain_encoder(output)
+    }
+
+    fn drain_graph(&mut self, output: &mut Format<Output>) -> Result<()> {
+        loop {
+            match self.graph.get_output_into(&self.sink, &mut self.filtered) {
+                Ok(()) => {}
+                Err(Error::SubmitMoreInput) | Err(Error::EoF) => return Ok(()),
+                Err(x) => return Err(x),
+            }
+
+            let pts = self.filtered.get_pts().map(|pts| {
+                pts.to_timestamp(self.sink_time_base)
+                    .with_new_timebase_rounded(self.encoder_time_base, Rounding::Nearest)
+                    .get_ticks()
+            });
+            self.filtered.set_pts(pts);
+
+            unsafe {
+                // Let the encoder choose which frames become keyframes
+                (*self.filtered.as_raw()).pict_type = AVPictureType::AV_PICTURE_TYPE_NONE;
+            }
+
+            self.encoder.submit_frame(&mut self.filtered)?;
+            self.drain_encoder(output)?;
+        }
+    }
+
+    fn drain_encoder(&mut self, output: &mut Format<Output>) -> Result<()> {
+        loop {
+            match self.encoder.get_next_packet_into(&mut self.encoded) {
+                Ok(()) => {}
+                Err(Error::SubmitMoreInput) | Err(Error::EoF) => return Ok(()),
+                Err(x) => return Err(x),
+            }
+
+            self.encoded.set_stream_index(self.out_index);
+            self.encoded
+                .rescale_ts(self.encoder_time_base, self.out_time_base);
+
+            output.write_packet(&mut self.encoded)?;
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use std::path::Path;
+
+use super::pipeline::{CopiedStream, Pipeline, StreamPipeline};
+use crate::{
+    config::Dictionary,
+    error::{Error, Result},
+    raw::{codec::EncoderBuilder, format::Format, packet::Packet, stream::Stream},
+    util::{
+        marker::{Audio, Input, Output, Unknown, Video},
+        MediaType,
+    },
+};
+
+/// What should be done with an input stream
+enum StreamAction {
+    Copy,
+    Skip,
+    Video(EncoderBuilder<Video>),
+    Audio(EncoderBuilder<Audio>),
+}
+
+/// Configuration for a `Transcoder`
+///
+/// By default all audio and video streams of the input are copied to the
+/// output without re-encoding and all other streams are skipped.
+pub struct TranscoderBuilder {
+    input: Format<Input>,
+    output: Format<Output>,
+    actions: Vec<(usize, StreamAction)>,
+    muxer_options: Dictionary,
+}
+
+impl TranscoderBuilder {
+    /// Start configuring a transcode from the `input` to the `output` Format
+    ///
+    /// No streams may have been added to the output Format.
+    pub fn new(input: Format<Input>, output: Format<Output>) -> TranscoderBuilder {
+        TranscoderBuilder {
+            input,
+            output,
+            actions: Vec::new(),
+            muxer_options: Dictionary::new(),
+        }
+    }
+
+    /// Start configuring a transcode from the `input` file to the `output` file
+    ///
+    /// The output format type will be determined by the output file extension
+    pub fn open<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q) -> Result<TranscoderBuilder> {
+        Ok(Self::new(
+            Format::open_input(input)?,
+            Format::open_output(output)?,
+        ))
+    }
+
+    /// Copy the input stream with index `index` without re-encoding it
+    pub fn copy_stream(mut self, index: usize) -> Self {
+        self.actions.push((index, StreamAction::Copy));
+        self
+    }
+
+    /// Leave the input stream with index `index` out of the output
+    pub fn skip_stream(mut self, index: usize) -> Self {
+        self.actions.push((index, StreamAction::Skip));
+        self
+    }
+
+    /// Re-encode the video stream with index `index` with the provided encoder
+    ///
+    /// Any settings of the encoder which aren't configured are taken from the
+    /// decoded stream. Frames are scaled to the frame size and converted to
+    /// the pixel format of the encoder.
+    pub fn transcode_video(mut self, index: usize, encoder: EncoderBuilder<Video>) -> Self {
+        self.actions.push((index, StreamAction::Video(encoder)));
+        self
+    }
+
+    /// Re-encode the audio stream with index `index` with the provided encoder
+    ///
+    /// Any settings of the encoder which aren't configured are taken from the
+    /// decoded stream. Audio is resampled to the configuration of the encoder.
+    pub fn transcode_audio(mut self, index: usize, encoder: EncoderBuilder<Audio>) -> Self {
+        self.actions.push((index, StreamAction::Audio(encoder)));
+        self
+    }
+
+    /// Set the options passed to the muxer when writing the output header
+    pub fn muxer_options(mut self, options: Dictionary) -> Self {
+        self.muxer_options = options;
+        self
+    }
+
+    /// Open all codecs and write the header of the output Format
+    pub fn build(self) -> Result<Transcoder> {
+        let TranscoderBuilder {
+            input,
+            mut output,
+            actions,
+            mut muxer_options,
+        } = self;
+
+        if output.get_configuration().num_streams() != 0 {
+            return Err(Error::InvalidArguments("setting up transcoder output"));
+        }
+
+        let mut plan: Vec<StreamAction> = input.streams().iter().map(default_action).collect();
+        for (index, action) in actions {
+            *plan
+                .get_mut(index)
+                .ok_or(Error::InvalidStreamIndex(index))? = action;
+        }
+
+        let mut streams = Vec::with_capacity(plan.len());
+        for (stream, action) in input.streams().iter().zip(plan) {
+            let pipeline = match action {
+                StreamAction::Skip => None,
+                StreamAction::Copy => Some(StreamPipeline::Copy(CopiedStream::new(
+                    stream,
+                    &mut output,
+                )?)),
+                StreamAction::Video(encoder) => Some(StreamPipeline::Video(Box::new(
+                    Pipeline::new_video(&typed_stream(*stream)?, encoder, &mut output)?,
+                ))),
+                StreamAction::Audio(encoder) => Some(StreamPipeline::Audio(Box::new(
+                    Pipeline::new_audio(&typed_stream(*stream)?, encoder, &mut output)?,
+                ))),
+            };
+
+            streams.push(pipeline);
+        }
+
+        if streams.iter().all(Option::is_none) {
+            return Err(Error::StreamNotFound);
+        }
+
+        output.write_header_with_options(&mut muxer_options)?;
+
+        // The muxer may have chosen different TimeBases when writing the header
+        for pipeline in streams.iter_mut().flatten() {
+            let time_base = output.streams()[pipeline.output_index()].time_base();
+            pipeline.set_output_time_base(time_base);
+        }
+
+        Ok(Transcoder {
+            input,
+            output,
+            streams,
+            packet: Packet::new(),
+            finished: false,
+        })
+    }
+}
+
+/// Convert streams from an input Format to an output Format, re-encoding or
+/// copying each stream as configured by a `TranscoderBuilder`
+pub struct Transcoder {
+    input: Format<Input>,
+    output: Format<Output>,
+    // Indexed by the input stream index, `None` for streams which are skipped
+    streams: Vec<Option<StreamPipeline>>,
+    packet: Packet,
+    // Set once the end of the input has been reached and all pipelines drained
+    finished: bool,
+}
+
+impl Transcoder {
+    /// Process the next packet of the input
+    ///
+    /// Any packets produced are written to the output. Returns `Error::EoF`
+    /// once the end of the input has been reached and all buffered data has
+    /// been written.
+    pub fn transcode_next_packet(&mut self) -> Result<()> {
+        if self.finished {
+            return Err(Error::EoF);
+        }
+
+        loop {
+            match self.input.get_next_packet_into(&mut self.packet) {
+                Ok(()) => {}
+                Err(Error::EoF) => {
+                    self.finished = true;
+                    self.finish_streams()?;
+                    return Err(Error::EoF);
+                }
+                Err(x) => return Err(x),
+            }
+
+            match self.streams.get_mut(self.packet.stream_index()) {
+                Some(Some(pipeline)) => {
+                    return pipeline.submit_packet(&mut self.packet, &mut self.output)
+                }
+                // Skip packets from streams that aren't in the output
+                _ => continue,
+            }
+        }
+    }
+
+    /// Process all remaining packets of the input
+    pub fn run(&mut self) -> Result<()> {
+        loop {
+            match self.transcode_next_packet() {
+                Ok(()) => {}
+                Err(Error::EoF) => return Ok(()),
+                Err(x) => return Err(x),
+            }
+        }
+    }
+
+    /// Process all remaining packets and finalize the output
+    pub fn finish(mut self) -> Result<()> {
+        self.run()?;
+        self.output.finish()
+    }
+
+    /// Process all remaining packets, finalize the output and return the writer
+    /// the output Format was opened with
+    ///
+    /// See `Format::finish_into_writer()` for details.
+    pub fn finish_into_writer<W: 'static>(mut self) -> Result<W> {
+        self.run()?;
+        self.output.finish_into_writer()
+    }
+
+    /// Get the index of the output stream which the input stream with index
+    /// `input_index` is written to, `None` if the stream is skipped
+    pub fn output_stream_index(&self, input_index: usize) -> Option<usize> {
+        self.streams
+            .get(input_index)
+            .and_then(Option::as_ref)
+            .map(StreamPipeline::output_index)
+    }
+
+    /// Get the input Format
+    pub fn input(&self) -> &Format<Input> {
+        &self.input
+    }
+
+    /// Get the output Format
+    pub fn output(&self) -> &Format<Output> {
+        &self.output
+    }
+
+    fn finish_streams(&mut self) -> Result<()> {
+        for pipeline in self.streams.iter_mut().flatten() {
+            pipeline.finish(&mut self.output)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn default_action(stream: &Stream<Unknown>) -> StreamAction {
+    if stream.is_audio() || stream.is_video() {
+        StreamAction::Copy
+    } else {
+        StreamAction::Skip
+    }
+}
+
+fn typed_stream<T: MediaType>(stream: Stream<Unknown>) -> Result<Stream<T>> {
+    let found = stream.media_type();
+
+    stream.try_as_type().ok_or(Error::CodecTypeMismatch {
+        expected: T::MEDIA_TYPE,
+        found,
+    })
+}
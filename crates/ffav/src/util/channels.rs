@@ -14,6 +14,10 @@ use ffav_sys::{
 
 bitflags! {
     /// The Layout of channels in an Audio stream
+    ///
+    /// Where channels are listed in order, eg. the rows of a mixing matrix,
+    /// they are ordered as they appear in the bits of the layout, from the
+    /// lowest bit set.
     pub struct ChannelLayout: u64 {
         /// The Front left speaker
         const FRONT_LEFT = AV_CH_FRONT_LEFT as u64;
@@ -0,0 +1,109 @@
+use ffav_sys::{AVColorRange, AVColorSpace};
+
+/// The matrix used to convert between RGB and YUV representations of a color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// Unspecified color space
+    Unspecified,
+    /// The identity matrix, used for RGB and GBR data
+    RGB,
+    /// also ITU-R BT1361 / IEC 61966-2-4 xvYCC709 / SMPTE RP177 Annex B
+    BT709,
+    /// FCC Title 47 Code of Federal Regulations 73.682 (a)(20)
+    FCC,
+    /// also ITU-R BT601-6 625 / ITU-R BT1358 625 / ITU-R BT1700 625 PAL & SECAM
+    BT470BG,
+    /// also ITU-R BT601-6 525 / ITU-R BT1358 525 / ITU-R BT1700 NTSC
+    SMPTE170M,
+    /// derived from 170M primaries and D65 white point
+    SMPTE240M,
+    /// Used by Dirac / VC-2 and H.264 FRext
+    YCGCO,
+    /// ITU-R BT2020 non-constant luminance system
+    BT2020NCL,
+    /// ITU-R BT2020 constant luminance system
+    BT2020CL,
+    /// SMPTE 2085, Y'D'zD'x
+    SMPTE2085,
+    /// Chromaticity-derived non-constant luminance system
+    ChromaDerivedNCL,
+    /// Chromaticity-derived constant luminance system
+    ChromaDerivedCL,
+    /// ITU-R BT.2100-0, ICtCp
+    ICTCP,
+}
+
+impl From<AVColorSpace> for ColorSpace {
+    fn from(space: AVColorSpace) -> Self {
+        match space {
+            AVColorSpace::AVCOL_SPC_RGB => ColorSpace::RGB,
+            AVColorSpace::AVCOL_SPC_BT709 => ColorSpace::BT709,
+            AVColorSpace::AVCOL_SPC_FCC => ColorSpace::FCC,
+            AVColorSpace::AVCOL_SPC_BT470BG => ColorSpace::BT470BG,
+            AVColorSpace::AVCOL_SPC_SMPTE170M => ColorSpace::SMPTE170M,
+            AVColorSpace::AVCOL_SPC_SMPTE240M => ColorSpace::SMPTE240M,
+            AVColorSpace::AVCOL_SPC_YCGCO => ColorSpace::YCGCO,
+            AVColorSpace::AVCOL_SPC_BT2020_NCL => ColorSpace::BT2020NCL,
+            AVColorSpace::AVCOL_SPC_BT2020_CL => ColorSpace::BT2020CL,
+            AVColorSpace::AVCOL_SPC_SMPTE2085 => ColorSpace::SMPTE2085,
+            AVColorSpace::AVCOL_SPC_CHROMA_DERIVED_NCL => ColorSpace::ChromaDerivedNCL,
+            AVColorSpace::AVCOL_SPC_CHROMA_DERIVED_CL => ColorSpace::ChromaDerivedCL,
+            AVColorSpace::AVCOL_SPC_ICTCP => ColorSpace::ICTCP,
+            // Reserved values can come straight from the bitstream, so treat
+            // them as unknown rather than failing
+            _ => ColorSpace::Unspecified,
+        }
+    }
+}
+
+impl From<ColorSpace> for AVColorSpace {
+    fn from(space: ColorSpace) -> Self {
+        match space {
+            ColorSpace::Unspecified => AVColorSpace::AVCOL_SPC_UNSPECIFIED,
+            ColorSpace::RGB => AVColorSpace::AVCOL_SPC_RGB,
+            ColorSpace::BT709 => AVColorSpace::AVCOL_SPC_BT709,
+            ColorSpace::FCC => AVColorSpace::AVCOL_SPC_FCC,
+            ColorSpace::BT470BG => AVColorSpace::AVCOL_SPC_BT470BG,
+            ColorSpace::SMPTE170M => AVColorSpace::AVCOL_SPC_SMPTE170M,
+            ColorSpace::SMPTE240M => AVColorSpace::AVCOL_SPC_SMPTE240M,
+            ColorSpace::YCGCO => AVColorSpace::AVCOL_SPC_YCGCO,
+            ColorSpace::BT2020NCL => AVColorSpace::AVCOL_SPC_BT2020_NCL,
+            ColorSpace::BT2020CL => AVColorSpace::AVCOL_SPC_BT2020_CL,
+            ColorSpace::SMPTE2085 => AVColorSpace::AVCOL_SPC_SMPTE2085,
+            ColorSpace::ChromaDerivedNCL => AVColorSpace::AVCOL_SPC_CHROMA_DERIVED_NCL,
+            ColorSpace::ChromaDerivedCL => AVColorSpace::AVCOL_SPC_CHROMA_DERIVED_CL,
+            ColorSpace::ICTCP => AVColorSpace::AVCOL_SPC_ICTCP,
+        }
+    }
+}
+
+/// The range of values used to represent each color component
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorRange {
+    /// Unspecified color range
+    Unspecified,
+    /// The "MPEG" or "TV" range, eg. 16-235 for 8-bit luma
+    Limited,
+    /// The "JPEG" or "PC" range, the full range of the component's bit depth
+    Full,
+}
+
+impl From<AVColorRange> for ColorRange {
+    fn from(range: AVColorRange) -> Self {
+        match range {
+            AVColorRange::AVCOL_RANGE_MPEG => ColorRange::Limited,
+            AVColorRange::AVCOL_RANGE_JPEG => ColorRange::Full,
+            _ => ColorRange::Unspecified,
+        }
+    }
+}
+
+impl From<ColorRange> for AVColorRange {
+    fn from(range: ColorRange) -> Self {
+        match range {
+            ColorRange::Unspecified => AVColorRange::AVCOL_RANGE_UNSPECIFIED,
+            ColorRange::Limited => AVColorRange::AVCOL_RANGE_MPEG,
+            ColorRange::Full => AVColorRange::AVCOL_RANGE_JPEG,
+        }
+    }
+}
@@ -1,29 +1,311 @@
 use crate::{
+    error::Result,
     raw::frame::Frame,
-    util::{color::PixelFormat, marker::Video},
+    util::{
+        color::{Endian, PixelFormat},
+        marker::Video,
+    },
 };
-use image::RgbImage;
+use image::{DynamicImage, ImageBuffer, Luma, LumaA, Pixel, Primitive, Rgb, Rgba};
 
+/// An image type which can be converted to and from video frames
 pub trait ImageFormat: Sized {
-    const FORMAT: PixelFormat;
+    /// The pixel format frames in the `source` pixel format should be
+    /// converted to before calling `from_frame()`
+    ///
+    /// Image types with a fixed layout always return the same format.
+    fn pixel_format_for(source: PixelFormat) -> PixelFormat;
 
+    /// Copy the image out of a frame
+    ///
+    /// The frame must have the pixel format returned by `pixel_format_for()`.
     fn from_frame(frame: &Frame<Video>) -> Self;
+
+    /// Copy the image into a new frame
+    fn into_frame(self) -> Result<Frame<Video>>;
+}
+
+/// A single color component which can be read from raw frame data
+///
+/// Multi-byte components are stored in frames in the native endianness.
+trait Component: Primitive + 'static {
+    const SIZE: usize;
+
+    fn from_ne_bytes(bytes: &[u8]) -> Self;
+
+    fn write_ne_bytes(self, out: &mut [u8]);
+}
+
+impl Component for u8 {
+    const SIZE: usize = 1;
+
+    fn from_ne_bytes(bytes: &[u8]) -> Self {
+        bytes[0]
+    }
+
+    fn write_ne_bytes(self, out: &mut [u8]) {
+        out[0] = self;
+    }
+}
+
+impl Component for u16 {
+    const SIZE: usize = 2;
+
+    fn from_ne_bytes(bytes: &[u8]) -> Self {
+        u16::from_ne_bytes([bytes[0], bytes[1]])
+    }
+
+    fn write_ne_bytes(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.to_ne_bytes());
+    }
+}
+
+impl Component for f32 {
+    const SIZE: usize = 4;
+
+    fn from_ne_bytes(bytes: &[u8]) -> Self {
+        f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    fn write_ne_bytes(self, out: &mut [u8]) {
+        out.copy_from_slice(&self.to_ne_bytes());
+    }
 }
 
-impl ImageFormat for RgbImage {
-    const FORMAT: PixelFormat = PixelFormat::RGB24;
+/// Implement `ImageFormat` for an image with all components packed in one plane
+macro_rules! packed_image_format {
+    ($pixel:ty, $format:expr) => {
+        impl ImageFormat for ImageBuffer<$pixel, Vec<<$pixel as Pixel>::Subpixel>> {
+            fn pixel_format_for(_source: PixelFormat) -> PixelFormat {
+                $format
+            }
+
+            fn from_frame(frame: &Frame<Video>) -> Self {
+                packed_from_frame(frame)
+            }
+
+            fn into_frame(self) -> Result<Frame<Video>> {
+                packed_into_frame(&self, $format)
+            }
+        }
+    };
+}
+
+/// Implement `ImageFormat` for an image read from a planar format, `$planes`
+/// lists the frame plane holding each component of the pixel in order
+macro_rules! planar_image_format {
+    ($pixel:ty, $format:expr, $planes:expr) => {
+        impl ImageFormat for ImageBuffer<$pixel, Vec<<$pixel as Pixel>::Subpixel>> {
+            fn pixel_format_for(_source: PixelFormat) -> PixelFormat {
+                $format
+            }
+
+            fn from_frame(frame: &Frame<Video>) -> Self {
+                planar_from_frame(frame, &$planes)
+            }
 
+            fn into_frame(self) -> Result<Frame<Video>> {
+                planar_into_frame(&self, $format, &$planes)
+            }
+        }
+    };
+}
+
+packed_image_format!(Rgb<u8>, PixelFormat::RGB24);
+packed_image_format!(Rgba<u8>, PixelFormat::RGBA);
+packed_image_format!(Luma<u8>, PixelFormat::GRAY8);
+packed_image_format!(LumaA<u8>, PixelFormat::YA8);
+packed_image_format!(Rgb<u16>, PixelFormat::RGB48(Endian::NATIVE));
+packed_image_format!(Rgba<u16>, PixelFormat::RGBA64(Endian::NATIVE));
+packed_image_format!(Luma<u16>, PixelFormat::GRAY16(Endian::NATIVE));
+packed_image_format!(LumaA<u16>, PixelFormat::YA16(Endian::NATIVE));
+packed_image_format!(Luma<f32>, PixelFormat::GRAYF32(Endian::NATIVE));
+// libav has no packed float RGB formats, the GBR planar formats are used instead
+planar_image_format!(Rgb<f32>, PixelFormat::GBRPF32(Endian::NATIVE), [2, 0, 1]);
+planar_image_format!(
+    Rgba<f32>,
+    PixelFormat::GBRAPF32(Endian::NATIVE),
+    [2, 0, 1, 3]
+);
+
+/// Frames are converted to the `DynamicImage` variant which can hold the
+/// source pixel format without losing precision or alpha
+///
+/// Gray frames keep a single channel, except for float formats which are
+/// converted to RGB as `DynamicImage` has no gray float variants.
+impl ImageFormat for DynamicImage {
+    fn pixel_format_for(source: PixelFormat) -> PixelFormat {
+        let gray = source.color_components() == 1;
+        let alpha = source.has_alpha();
+
+        if source.is_float() {
+            // There are no gray float variants, gray is expanded to RGB
+            if alpha {
+                PixelFormat::GBRAPF32(Endian::NATIVE)
+            } else {
+                PixelFormat::GBRPF32(Endian::NATIVE)
+            }
+        } else if source.max_component_depth() > 8 {
+            match (gray, alpha) {
+                (true, false) => PixelFormat::GRAY16(Endian::NATIVE),
+                (true, true) => PixelFormat::YA16(Endian::NATIVE),
+                (false, false) => PixelFormat::RGB48(Endian::NATIVE),
+                (false, true) => PixelFormat::RGBA64(Endian::NATIVE),
+            }
+        } else {
+            match (gray, alpha) {
+                (true, false) => PixelFormat::GRAY8,
+                (true, true) => PixelFormat::YA8,
+                (false, false) => PixelFormat::RGB24,
+                (false, true) => PixelFormat::RGBA,
+            }
+        }
+    }
+
+    /// # Panics
+    /// If the frame isn't in one of the pixel formats returned by
+    /// `pixel_format_for()`
     fn from_frame(frame: &Frame<Video>) -> Self {
-        let plane = frame.plane(0);
+        match frame.pixel_format() {
+            PixelFormat::GRAY8 => DynamicImage::ImageLuma8(ImageFormat::from_frame(frame)),
+            PixelFormat::YA8 => DynamicImage::ImageLumaA8(ImageFormat::from_frame(frame)),
+            PixelFormat::RGB24 => DynamicImage::ImageRgb8(ImageFormat::from_frame(frame)),
+            PixelFormat::RGBA => DynamicImage::ImageRgba8(ImageFormat::from_frame(frame)),
+            PixelFormat::GRAY16(e) if e == Endian::NATIVE => {
+                DynamicImage::ImageLuma16(ImageFormat::from_frame(frame))
+            }
+            PixelFormat::YA16(e) if e == Endian::NATIVE => {
+                DynamicImage::ImageLumaA16(ImageFormat::from_frame(frame))
+            }
+            PixelFormat::RGB48(e) if e == Endian::NATIVE => {
+                DynamicImage::ImageRgb16(ImageFormat::from_frame(frame))
+            }
+            PixelFormat::RGBA64(e) if e == Endian::NATIVE => {
+                DynamicImage::ImageRgba16(ImageFormat::from_frame(frame))
+            }
+            PixelFormat::GBRPF32(e) if e == Endian::NATIVE => {
+                DynamicImage::ImageRgb32F(ImageFormat::from_frame(frame))
+            }
+            PixelFormat::GBRAPF32(e) if e == Endian::NATIVE => {
+                DynamicImage::ImageRgba32F(ImageFormat::from_frame(frame))
+            }
+            format => panic!("No DynamicImage variant for the pixel format {}", format),
+        }
+    }
+
+    fn into_frame(self) -> Result<Frame<Video>> {
+        match self {
+            DynamicImage::ImageLuma8(image) => image.into_frame(),
+            DynamicImage::ImageLumaA8(image) => image.into_frame(),
+            DynamicImage::ImageRgb8(image) => image.into_frame(),
+            DynamicImage::ImageRgba8(image) => image.into_frame(),
+            DynamicImage::ImageLuma16(image) => image.into_frame(),
+            DynamicImage::ImageLumaA16(image) => image.into_frame(),
+            DynamicImage::ImageRgb16(image) => image.into_frame(),
+            DynamicImage::ImageRgba16(image) => image.into_frame(),
+            DynamicImage::ImageRgb32F(image) => image.into_frame(),
+            DynamicImage::ImageRgba32F(image) => image.into_frame(),
+            // Any variants added in the future are converted losslessly
+            other => other.into_rgba32f().into_frame(),
+        }
+    }
+}
+
+fn packed_from_frame<P>(frame: &Frame<Video>) -> ImageBuffer<P, Vec<P::Subpixel>>
+where
+    P: Pixel + 'static,
+    P::Subpixel: Component,
+{
+    let plane = frame.plane(0).expect("Frame has no image data");
+    let row_size = frame.width() as usize * P::CHANNEL_COUNT as usize * P::Subpixel::SIZE;
+
+    let components: Vec<P::Subpixel> = plane
+        .chunks_exact(plane.linesize)
+        .flat_map(|line| line[..row_size].chunks_exact(P::Subpixel::SIZE))
+        .map(P::Subpixel::from_ne_bytes)
+        .collect();
+
+    ImageBuffer::from_raw(frame.width(), frame.height(), components)
+        .expect("Frame data was not of the right dimensions")
+}
 
-        let pixels: Vec<u8> = plane
-            .chunks_exact(plane.linesize)
-            .flat_map(|line| line.chunks_exact(3).take(frame.width() as usize))
-            .flatten()
-            .copied()
-            .collect();
+fn packed_into_frame<P>(
+    image: &ImageBuffer<P, Vec<P::Subpixel>>,
+    format: PixelFormat,
+) -> Result<Frame<Video>>
+where
+    P: Pixel + 'static,
+    P::Subpixel: Component,
+{
+    let mut frame = Frame::new_video(image.width(), image.height(), format)?;
+    let row_len = image.width() as usize * P::CHANNEL_COUNT as usize;
 
-        RgbImage::from_vec(frame.width(), frame.height(), pixels)
-            .expect("Frame data was not of the right dimensions")
+    let mut plane = frame.plane_mut(0)?;
+    let linesize = plane.linesize;
+
+    for (line, row) in plane
+        .chunks_exact_mut(linesize)
+        .zip(image.as_raw().chunks_exact(row_len))
+    {
+        for (out, component) in line.chunks_exact_mut(P::Subpixel::SIZE).zip(row) {
+            component.write_ne_bytes(out);
+        }
     }
+
+    Ok(frame)
+}
+
+fn planar_from_frame<P>(frame: &Frame<Video>, planes: &[usize]) -> ImageBuffer<P, Vec<P::Subpixel>>
+where
+    P: Pixel + 'static,
+    P::Subpixel: Component,
+{
+    let (width, height) = (frame.width() as usize, frame.height() as usize);
+    let planes: Vec<_> = planes
+        .iter()
+        .map(|&index| frame.plane(index).expect("Frame has no image data"))
+        .collect();
+
+    let mut components = Vec::with_capacity(width * height * planes.len());
+    for y in 0..height {
+        for x in 0..width {
+            for plane in &planes {
+                let offset = y * plane.linesize + x * P::Subpixel::SIZE;
+                components.push(P::Subpixel::from_ne_bytes(&plane[offset..]));
+            }
+        }
+    }
+
+    ImageBuffer::from_raw(frame.width(), frame.height(), components)
+        .expect("Frame data was not of the right dimensions")
+}
+
+fn planar_into_frame<P>(
+    image: &ImageBuffer<P, Vec<P::Subpixel>>,
+    format: PixelFormat,
+    planes: &[usize],
+) -> Result<Frame<Video>>
+where
+    P: Pixel + 'static,
+    P::Subpixel: Component,
+{
+    let mut frame = Frame::new_video(image.width(), image.height(), format)?;
+
+    for (component, &index) in planes.iter().enumerate() {
+        let mut plane = frame.plane_mut(index)?;
+        let linesize = plane.linesize;
+
+        for (y, line) in plane.chunks_exact_mut(linesize).enumerate() {
+            for (x, out) in line
+                .chunks_exact_mut(P::Subpixel::SIZE)
+                .take(image.width() as usize)
+                .enumerate()
+            {
+                let pixel = image.get_pixel(x as u32, y as u32);
+                pixel.channels()[component].write_ne_bytes(out);
+            }
+        }
+    }
+
+    Ok(frame)
 }
@@ -1,9 +1,11 @@
 mod color_primary;
+mod color_space;
 #[cfg(feature = "image-decode")]
 mod image_ext;
 mod pixel_format;
 
 pub use color_primary::*;
+pub use color_space::*;
 #[cfg(feature = "image-decode")]
 pub use image_ext::*;
 pub use pixel_format::*;
@@ -16,3 +18,12 @@ pub enum Endian {
     /// Stored in big-endian format
     Little,
 }
+
+impl Endian {
+    /// The endianness of the target platform
+    #[cfg(target_endian = "little")]
+    pub const NATIVE: Endian = Endian::Little;
+    /// The endianness of the target platform
+    #[cfg(target_endian = "big")]
+    pub const NATIVE: Endian = Endian::Big;
+}
@@ -1,6 +1,9 @@
 #![allow(non_camel_case_types)]
 use super::Endian;
-use ffav_sys::{av_get_pix_fmt_name, AVPixelFormat};
+use ffav_sys::{
+    av_get_pix_fmt_name, av_pix_fmt_desc_get, AVPixFmtDescriptor, AVPixelFormat,
+    AV_PIX_FMT_FLAG_ALPHA, AV_PIX_FMT_FLAG_FLOAT, AV_PIX_FMT_FLAG_RGB,
+};
 use std::{borrow::Cow, ffi::CStr, fmt};
 
 pub enum ColorEncoding {
@@ -327,6 +330,51 @@ impl PixelFormat {
     pub fn format_name(&self) -> Cow<str> {
         unsafe { CStr::from_ptr(av_get_pix_fmt_name(self.into())).to_string_lossy() }
     }
+
+    /// Check if this pixel format has an alpha component
+    pub fn has_alpha(&self) -> bool {
+        self.descriptor()
+            .map_or(false, |desc| desc.flags & AV_PIX_FMT_FLAG_ALPHA as u64 != 0)
+    }
+
+    /// Check if the components of this pixel format are floating point values
+    pub fn is_float(&self) -> bool {
+        self.descriptor()
+            .map_or(false, |desc| desc.flags & AV_PIX_FMT_FLAG_FLOAT as u64 != 0)
+    }
+
+    /// Check if this pixel format stores RGB components rather than YUV or
+    /// grayscale
+    pub fn is_rgb(&self) -> bool {
+        self.descriptor()
+            .map_or(false, |desc| desc.flags & AV_PIX_FMT_FLAG_RGB as u64 != 0)
+    }
+
+    /// Get the number of color components, not counting the alpha component
+    ///
+    /// This is 1 for grayscale formats and 3 for RGB and YUV formats
+    pub fn color_components(&self) -> u32 {
+        self.descriptor().map_or(0, |desc| {
+            desc.nb_components as u32 - if self.has_alpha() { 1 } else { 0 }
+        })
+    }
+
+    /// Get the largest number of bits used to store a single component
+    pub fn max_component_depth(&self) -> u32 {
+        self.descriptor().map_or(0, |desc| {
+            desc.comp[..desc.nb_components as usize]
+                .iter()
+                .map(|comp| comp.depth as u32)
+                .max()
+                .unwrap_or(0)
+        })
+    }
+
+    fn descriptor(&self) -> Option<&'static AVPixFmtDescriptor> {
+        // SAFETY: Descriptors are static data owned by libav, NULL is returned
+        // for `AV_PIX_FMT_NONE`
+        unsafe { av_pix_fmt_desc_get(self.into()).as_ref() }
+    }
 }
 
 impl From<&PixelFormat> for AVPixelFormat {
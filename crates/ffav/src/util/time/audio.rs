@@ -1,6 +1,10 @@
-use crate::util::marker::Audio;
+use crate::{
+    config::StreamConfig,
+    error::{Error, Result},
+    util::marker::Audio,
+};
 
-use super::{TimeBase, TimeBaseTicks};
+use super::{Rounding, TimeBase, TimeBaseTicks, Timestamp};
 
 /// The sampling rate of an audio stream as an integer number of Hz
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -16,6 +20,18 @@ impl SampleRate {
     pub fn as_hz(self) -> u32 {
         self.0
     }
+
+    /// The TimeBase in which each tick is the duration of one sample
+    ///
+    /// Returns None if the sample rate is zero, which libav uses for an
+    /// unknown sample rate
+    pub fn sample_time_base(self) -> Option<TimeBase> {
+        if self.0 == 0 {
+            None
+        } else {
+            Some(TimeBase::new(1, self.0 as u64))
+        }
+    }
 }
 
 /// The sample number of a single sample from an Audio stream
@@ -23,8 +39,59 @@ impl SampleRate {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Sample(u64);
 
+impl Sample {
+    /// Create a new sample index, the first sample of a stream has index 0
+    pub fn new(index: u64) -> Self {
+        Sample(index)
+    }
+
+    /// Get the index of this sample
+    pub fn index(self) -> u64 {
+        self.0
+    }
+
+    /// Find the sample of a stream nearest to a timestamp
+    ///
+    /// This is the inverse of `IntoStreamTimestamp`, the sample index is
+    /// counted from the start time of the stream when it is known. Timestamps
+    /// before the start of the stream result in the first sample.
+    pub fn from_stream_timestamp(ts: Timestamp, stream: &StreamConfig<Audio>) -> Result<Sample> {
+        let base = stream
+            .sample_rate()
+            .sample_time_base()
+            .ok_or(Error::UnknownSampleRate)?;
+
+        let ts = match stream.start_time() {
+            Some(start) => ts - start,
+            None => ts,
+        };
+
+        let index = ts
+            .with_new_timebase_rounded(base, Rounding::Nearest)
+            .get_ticks();
+
+        Ok(Sample::new(index.as_av_timestamp().max(0) as u64))
+    }
+}
+
+/// The sample index is counted from the start time of the stream, when known,
+/// and rounded to the nearest tick of the stream's TimeBase. The inverse is
+/// `Sample::from_stream_timestamp()`.
 impl super::IntoStreamTimestamp<Audio> for Sample {
-    fn into(self, stream: &crate::config::StreamConfig<Audio>) -> super::Timestamp {
-        TimeBaseTicks::new(self.0).to_timestamp(TimeBase::new(1, stream.sample_rate().0 as u64))
+    fn into(self, stream: &StreamConfig<Audio>) -> Result<Timestamp> {
+        let base = stream
+            .sample_rate()
+            .sample_time_base()
+            .ok_or(Error::UnknownSampleRate)?;
+
+        let ts = TimeBaseTicks::new(self.0 as i64)
+            .to_timestamp(base)
+            .with_new_timebase_rounded(stream.time_base(), Rounding::Nearest);
+
+        // The start time is already in the stream's TimeBase so the sum is too
+        Ok(match stream.start_time() {
+            Some(start) => ts + start,
+            None => ts,
+        })
     }
 }
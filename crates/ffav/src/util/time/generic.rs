@@ -1,8 +1,16 @@
 //! Utility structs and functions for dealing with time
 
 use super::{audio::*, video::*, Rational};
-use ffav_sys::{AVRational, AV_TIME_BASE};
-use std::{fmt, ops::Rem, time::Duration};
+use ffav_sys::{AVRational, AV_NOPTS_VALUE, AV_TIME_BASE};
+use num_rational::Ratio;
+use std::{
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Add, Rem, Sub},
+    time::Duration,
+};
 
 /// The minimum temporal resolution for timestamps of a Stream or Format
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -13,16 +21,101 @@ impl TimeBase {
     pub const DEFAULT: TimeBase = TimeBase(Rational::new_raw(1, AV_TIME_BASE as u64));
     /// A TimeBase in milliseconds
     pub const MILLISECONDS: TimeBase = TimeBase(Rational::new_raw(1, 1000));
+    /// The TimeBase libav gives to newly created streams
+    pub(crate) const NEW_STREAM: TimeBase = TimeBase(Rational::new_raw(1, 90000));
 
     /// Create a time-base from an `AVRational` type
-    pub(crate) fn from_av_rational(rational: &AVRational) -> Self {
-        TimeBase(Rational::new(rational.num as u64, rational.den as u64))
+    ///
+    /// libav uses `0/1` or `0/0` for an unset time base, these and any other
+    /// non-positive values result in None
+    pub(crate) fn from_av_rational(rational: &AVRational) -> Option<Self> {
+        if rational.num <= 0 || rational.den <= 0 {
+            None
+        } else {
+            Some(TimeBase::new(rational.num as u64, rational.den as u64))
+        }
     }
 
     /// Create a new TimeBase
+    ///
+    /// Panics: If `numer` or `denom` is zero
     pub fn new(numer: u64, denom: u64) -> Self {
+        assert!(numer != 0, "TimeBase numerator is zero");
+
         TimeBase(Rational::new(numer, denom))
     }
+
+    /// Convert this TimeBase to an `AVRational` for use with libav functions
+    pub(crate) fn as_av_rational(self) -> AVRational {
+        AVRational {
+            num: *self.0.numer() as i32,
+            den: *self.0.denom() as i32,
+        }
+    }
+
+    fn numer(self) -> i128 {
+        *self.0.numer() as i128
+    }
+
+    fn denom(self) -> i128 {
+        *self.0.denom() as i128
+    }
+
+    /// Find the coarsest TimeBase in which timestamps of both TimeBases can be
+    /// exactly represented
+    ///
+    /// Returns None if that TimeBase couldn't be used with libav functions.
+    fn common(self, other: TimeBase) -> Option<TimeBase> {
+        let (a, b) = (self.0, other.0);
+
+        let numer = gcd(*a.numer(), *b.numer());
+        let denom = (*a.denom() / gcd(*a.denom(), *b.denom())).checked_mul(*b.denom())?;
+
+        if numer != 0 && numer <= i32::MAX as u64 && denom <= i32::MAX as u64 {
+            Some(TimeBase::new(numer, denom))
+        } else {
+            None
+        }
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// How to round a Timestamp which can't be exactly represented in a new TimeBase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rounding {
+    /// Round towards zero
+    Zero,
+    /// Round towards negative infinity, to the nearest earlier time
+    Down,
+    /// Round towards positive infinity, to the nearest later time
+    Up,
+    /// Round to the nearest time, halfway cases are rounded away from zero
+    Nearest,
+}
+
+impl Rounding {
+    /// Divide `n` by the positive `d` with this rounding, returns None if the
+    /// result overflows
+    fn divide(self, n: i128, d: i128) -> Option<i128> {
+        match self {
+            Rounding::Zero => Some(n / d),
+            Rounding::Down => Some(n.div_euclid(d)),
+            Rounding::Up => n.checked_neg()?.div_euclid(d).checked_neg(),
+            Rounding::Nearest => {
+                let twice = n.checked_abs()?.checked_mul(2)?.checked_add(d)?;
+
+                Some(n.signum() * (twice / d.checked_mul(2)?))
+            }
+        }
+    }
 }
 
 impl fmt::Debug for TimeBase {
@@ -41,16 +134,33 @@ impl fmt::Display for TimeBase {
 /// TimeBase units.
 ///
 /// Without an associated TimeBase this does not provide any useful
-/// information and two instances cannot be compared
+/// information and two instances cannot be compared. The count may be negative
+/// for times before the start of a stream.
 #[derive(Debug, Clone, Copy, Hash)]
-pub struct TimeBaseTicks(u64);
+pub struct TimeBaseTicks(i64);
 
 impl TimeBaseTicks {
     /// Create aa new count of Ticks against an unknown TimeBase
-    pub fn new(ticks: u64) -> TimeBaseTicks {
+    pub fn new(ticks: i64) -> TimeBaseTicks {
         TimeBaseTicks(ticks)
     }
 
+    /// Convert a timestamp from libav, returns None if it is `AV_NOPTS_VALUE`
+    /// indicating that the time is unknown
+    pub(crate) fn from_av_timestamp(ts: i64) -> Option<TimeBaseTicks> {
+        if ts == AV_NOPTS_VALUE {
+            None
+        } else {
+            Some(TimeBaseTicks(ts))
+        }
+    }
+
+    /// Convert an optional count of ticks to a libav timestamp, using
+    /// `AV_NOPTS_VALUE` for an unknown time
+    pub(crate) fn option_as_av_timestamp(ticks: Option<TimeBaseTicks>) -> i64 {
+        ticks.map_or(AV_NOPTS_VALUE, TimeBaseTicks::as_av_timestamp)
+    }
+
     /// Associate this TimeBaseTicks with a TimeBase to get a true Timestamp
     pub fn to_timestamp(self, base: TimeBase) -> Timestamp {
         Timestamp { ticks: self, base }
@@ -58,14 +168,40 @@ impl TimeBaseTicks {
 
     /// Get the count of ticks as i64 for use with most libav functions
     pub fn as_av_timestamp(self) -> i64 {
-        self.0 as i64
+        self.0
+    }
+
+    fn as_i128(self) -> i128 {
+        self.0 as i128
+    }
+
+    /// Returns None if the value can't be represented
+    fn checked_from_i128(ticks: i128) -> Option<TimeBaseTicks> {
+        match i64::try_from(ticks) {
+            // This value is reserved by libav to mark an unknown time
+            Ok(ticks) if ticks != AV_NOPTS_VALUE => Some(TimeBaseTicks(ticks)),
+            _ => None,
+        }
+    }
+
+    /// The latest representable count of ticks if `positive`, otherwise the
+    /// earliest
+    fn saturated(positive: bool) -> TimeBaseTicks {
+        if positive {
+            TimeBaseTicks(i64::MAX)
+        } else {
+            // `i64::MIN` is `AV_NOPTS_VALUE`
+            TimeBaseTicks(i64::MIN + 1)
+        }
     }
 }
 
 /// A single point in time in a stream
 ///
 /// The resolution of this Timestamp is dependant on the associated TimeBase.
-#[derive(Debug, Clone, Copy, Hash)]
+/// Timestamps in different TimeBases can be compared and combined, the
+/// comparisons are exact.
+#[derive(Debug, Clone, Copy)]
 pub struct Timestamp {
     ticks: TimeBaseTicks,
     base: TimeBase,
@@ -75,23 +211,142 @@ impl Timestamp {
     /// Convert this `Timestamp` to using a different time base
     ///
     /// This may result in a loss of precision depending on the new TimeBase.
-    /// The new Timestamp should be the nearest valid time in the new TimeBase
+    /// The new Timestamp will be the nearest valid time in the new TimeBase
     /// prior to the old Timestamp
     pub fn with_new_timebase(self, other: TimeBase) -> Self {
+        self.with_new_timebase_rounded(other, Rounding::Down)
+    }
+
+    /// Convert this `Timestamp` to using a different time base, rounding as
+    /// specified if it can't be represented exactly
+    ///
+    /// If the new tick count can't be represented the Timestamp saturates at
+    /// the earliest or latest representable time, see
+    /// `checked_with_new_timebase_rounded()` to detect this.
+    pub fn with_new_timebase_rounded(self, other: TimeBase, rounding: Rounding) -> Self {
+        self.checked_with_new_timebase_rounded(other, rounding)
+            .unwrap_or(Timestamp {
+                ticks: TimeBaseTicks::saturated(!self.is_negative()),
+                base: other,
+            })
+    }
+
+    /// Convert this `Timestamp` to using a different time base, rounding as
+    /// specified if it can't be represented exactly
+    ///
+    /// Returns None if the new tick count can't be represented
+    pub fn checked_with_new_timebase_rounded(
+        self,
+        other: TimeBase,
+        rounding: Rounding,
+    ) -> Option<Self> {
         if self.base == other {
-            self
-        } else {
-            unimplemented!()
+            return Some(self);
+        }
+
+        // ticks * (base numer / base denom) / (other numer / other denom)
+        let numer = self
+            .ticks
+            .as_i128()
+            .checked_mul(self.base.numer())?
+            .checked_mul(other.denom())?;
+        let denom = self.base.denom().checked_mul(other.numer())?;
+
+        Some(Timestamp {
+            ticks: TimeBaseTicks::checked_from_i128(rounding.divide(numer, denom)?)?,
+            base: other,
+        })
+    }
+
+    /// Add two Timestamps exactly, the result is in a TimeBase that can
+    /// exactly represent both
+    ///
+    /// Returns None if the result can't be represented, or if there is no
+    /// TimeBase usable with libav which can represent both Timestamps. The
+    /// `+` operator rounds in that case instead.
+    pub fn checked_add(self, rhs: Timestamp) -> Option<Timestamp> {
+        self.combine(rhs, true, |lhs, rhs| lhs + rhs)
+    }
+
+    /// Subtract two Timestamps exactly, the result is in a TimeBase that can
+    /// exactly represent both
+    ///
+    /// Returns None if the result can't be represented, or if there is no
+    /// TimeBase usable with libav which can represent both Timestamps. The
+    /// `-` operator rounds in that case instead.
+    pub fn checked_sub(self, rhs: Timestamp) -> Option<Timestamp> {
+        self.combine(rhs, true, |lhs, rhs| lhs - rhs)
+    }
+
+    /// Get the TimeBase of this timestamp
+    pub fn time_base(self) -> TimeBase {
+        self.base
+    }
+
+    /// The exact point in time in seconds, used for comparisons
+    fn exact_secs(self) -> Ratio<i128> {
+        Ratio::new(self.ticks.as_i128() * self.base.numer(), self.base.denom())
+    }
+
+    /// The TimeBase which the sum or difference of two Timestamps uses
+    ///
+    /// If there is no usable common TimeBase the TimeBase of `self` is used.
+    fn common_base(self, rhs: Timestamp) -> TimeBase {
+        self.base.common(rhs.base).unwrap_or(self.base)
+    }
+
+    /// Apply `op` to the tick counts of both Timestamps in the
+    /// `common_base()`
+    ///
+    /// Without a usable common TimeBase `rhs` is rounded to the TimeBase of
+    /// `self`, unless `exact` is set in which case None is returned. Also
+    /// returns None if the result can't be represented.
+    fn combine(
+        self,
+        rhs: Timestamp,
+        exact: bool,
+        op: impl Fn(i128, i128) -> i128,
+    ) -> Option<Timestamp> {
+        if exact && self.base.common(rhs.base).is_none() {
+            return None;
         }
+
+        let base = self.common_base(rhs);
+        let lhs = self
+            .checked_with_new_timebase_rounded(base, Rounding::Nearest)?
+            .ticks
+            .as_i128();
+        let rhs = rhs
+            .checked_with_new_timebase_rounded(base, Rounding::Nearest)?
+            .ticks
+            .as_i128();
+
+        TimeBaseTicks::checked_from_i128(op(lhs, rhs)).map(|ticks| ticks.to_timestamp(base))
+    }
+
+    /// The same time before zero
+    fn negated(self) -> Timestamp {
+        TimeBaseTicks(self.ticks.0.saturating_neg()).to_timestamp(self.base)
     }
 
     /// Convert this `Timestamp` to a `std::time::Duration`
     ///
-    /// The provided duration will have millisecond resolution
+    /// The provided duration will have millisecond resolution. As a Duration
+    /// can't be negative, Timestamps before zero result in a zero Duration.
     pub fn as_duration(self) -> Duration {
         let millis = self.with_new_timebase(TimeBase::MILLISECONDS);
 
-        Duration::from_millis(millis.get_ticks().0)
+        Duration::from_millis(millis.get_ticks().0.max(0) as u64)
+    }
+
+    /// Convert this `Timestamp` to an approximate number of seconds
+    pub fn as_secs_f64(self) -> f64 {
+        self.ticks.0 as f64 * self.base.numer() as f64 / self.base.denom() as f64
+    }
+
+    /// Check if this Timestamp is before zero
+    pub fn is_negative(self) -> bool {
+        self.ticks.0 < 0
     }
 
     /// Get the underlying tick count of this timestamp in its current time-base
@@ -100,24 +355,40 @@ impl Timestamp {
     }
 
     /// Convert this `Timestamp` into a sample number given a known sample rate
-    pub fn sample(self, _sample_rate: SampleRate) -> Sample {
-        unimplemented!()
+    ///
+    /// The sample number returned will be the nearest sample before the `Timestamp`,
+    /// Timestamps before zero result in the first sample. Samples are counted
+    /// from time zero, use `Sample::from_stream_timestamp()` to count from the
+    /// start time of a stream.
+    ///
+    /// Returns None if the sample rate is zero
+    pub fn sample(self, sample_rate: SampleRate) -> Option<Sample> {
+        let base = sample_rate.sample_time_base()?;
+
+        Some(Sample::new(
+            self.with_new_timebase(base).ticks.0.max(0) as u64
+        ))
     }
 
     /// Convert this timestamp into the index of the corresponding frame
     ///
-    /// The frame number returned will be the nearest frame before the `Timestamp`
+    /// The frame number returned will be the nearest frame before the `Timestamp`,
+    /// Timestamps before zero result in the first frame. Frames are counted
+    /// from time zero, use `Frame::from_stream_timestamp()` to count from the
+    /// start time of a stream.
     /// NOTE: Because the `frame_rate` is typically an average, this may not always
     /// return the exact frame number.
-    pub fn frame(self, _frame_rate: ()) -> Frame {
-        unimplemented!()
+    pub fn frame(self, frame_rate: FrameRate) -> Frame {
+        let base = frame_rate.frame_time_base();
+
+        Frame::new(self.with_new_timebase(base).ticks.0.max(0) as u64)
     }
 }
 
 impl std::convert::From<Duration> for Timestamp {
     fn from(d: Duration) -> Self {
         Timestamp {
-            ticks: TimeBaseTicks::new(d.as_millis() as u64),
+            ticks: TimeBaseTicks::new(d.as_millis() as i64),
             base: TimeBase::MILLISECONDS,
         }
     }
@@ -125,8 +396,16 @@ impl std::convert::From<Duration> for Timestamp {
 
 impl fmt::Display for Timestamp {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let duration = self.as_duration();
-        let mut total_millis = duration.as_millis();
+        let millis = self
+            .with_new_timebase_rounded(TimeBase::MILLISECONDS, Rounding::Zero)
+            .get_ticks()
+            .0;
+
+        if millis < 0 {
+            write!(f, "-")?;
+        }
+
+        let mut total_millis = millis.unsigned_abs();
 
         let millis = total_millis.rem(1000);
         total_millis = (total_millis - millis) / 1000;
@@ -139,7 +418,70 @@ impl fmt::Display for Timestamp {
 
         let hours = total_millis;
 
-        write!(f, "{:02}:{:02}:{02}.{03}", hours, mins, secs, millis)
+        write!(f, "{:02}:{:02}:{:02}.{:03}", hours, mins, secs, millis)
+    }
+}
+
+impl Add for Timestamp {
+    type Output = Timestamp;
+
+    /// Add two Timestamps, the result is in a TimeBase that can exactly
+    /// represent both
+    ///
+    /// If no TimeBase usable with libav can represent both, `rhs` is rounded
+    /// to the nearest tick of the TimeBase of `self`. Saturates at the
+    /// earliest or latest representable time. See `checked_add()` to detect
+    /// either case.
+    fn add(self, rhs: Timestamp) -> Timestamp {
+        self.combine(rhs, false, |lhs, rhs| lhs + rhs)
+            .unwrap_or_else(|| {
+                TimeBaseTicks::saturated(self > rhs.negated()).to_timestamp(self.common_base(rhs))
+            })
+    }
+}
+
+impl Sub for Timestamp {
+    type Output = Timestamp;
+
+    /// Subtract two Timestamps, the result is in a TimeBase that can exactly
+    /// represent both
+    ///
+    /// If no TimeBase usable with libav can represent both, `rhs` is rounded
+    /// to the nearest tick of the TimeBase of `self`. Saturates at the
+    /// earliest or latest representable time. See `checked_sub()` to detect
+    /// either case.
+    fn sub(self, rhs: Timestamp) -> Timestamp {
+        self.combine(rhs, false, |lhs, rhs| lhs - rhs)
+            .unwrap_or_else(|| {
+                TimeBaseTicks::saturated(self > rhs).to_timestamp(self.common_base(rhs))
+            })
+    }
+}
+
+impl PartialEq for Timestamp {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Timestamp {}
+
+impl PartialOrd for Timestamp {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Timestamp {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.exact_secs().cmp(&other.exact_secs())
+    }
+}
+
+impl Hash for Timestamp {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Equal points in time must hash equally regardless of the TimeBase
+        self.exact_secs().hash(state);
     }
 }
 
@@ -159,3 +501,160 @@ impl fmt::Display for Timestamp {
 //         value.as_str().try_into()
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn ts(ticks: i64, numer: u64, denom: u64) -> Timestamp {
+        TimeBaseTicks::new(ticks).to_timestamp(TimeBase::new(numer, denom))
+    }
+
+    fn ticks(ts: Timestamp) -> i64 {
+        ts.get_ticks().as_av_timestamp()
+    }
+
+    fn hash(ts: Timestamp) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        ts.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    #[should_panic]
+    fn time_base_rejects_zero_numerator() {
+        TimeBase::new(0, 1);
+    }
+
+    #[test]
+    fn time_base_from_av_rational() {
+        let rational = |num, den| AVRational { num, den };
+
+        assert_eq!(
+            TimeBase::from_av_rational(&rational(1, 25)),
+            Some(TimeBase::new(1, 25))
+        );
+        assert_eq!(TimeBase::from_av_rational(&rational(0, 1)), None);
+        assert_eq!(TimeBase::from_av_rational(&rational(0, 0)), None);
+        assert_eq!(TimeBase::from_av_rational(&rational(-1, 25)), None);
+        assert_eq!(TimeBase::from_av_rational(&rational(1, -25)), None);
+    }
+
+    #[test]
+    fn rescale_exact() {
+        let rescaled = ts(90, 1, 90000).with_new_timebase(TimeBase::MILLISECONDS);
+
+        assert_eq!(ticks(rescaled), 1);
+        assert_eq!(rescaled.time_base(), TimeBase::MILLISECONDS);
+
+        let rescaled = ts(3, 1001, 30000).with_new_timebase(TimeBase::new(1, 90000));
+        assert_eq!(ticks(rescaled), 9009);
+    }
+
+    #[test]
+    fn rescale_rounding() {
+        let secs = TimeBase::new(1, 1);
+        let cases = [
+            (Rounding::Zero, 1, -1),
+            (Rounding::Down, 1, -2),
+            (Rounding::Up, 2, -1),
+            (Rounding::Nearest, 2, -2),
+        ];
+
+        for &(rounding, positive, negative) in cases.iter() {
+            let pos = ts(15, 1, 10).with_new_timebase_rounded(secs, rounding);
+            let neg = ts(-15, 1, 10).with_new_timebase_rounded(secs, rounding);
+
+            assert_eq!(ticks(pos), positive, "{:?}", rounding);
+            assert_eq!(ticks(neg), negative, "{:?}", rounding);
+        }
+
+        // Exact values are never rounded
+        for &(rounding, _, _) in cases.iter() {
+            assert_eq!(
+                ticks(ts(-20, 1, 10).with_new_timebase_rounded(secs, rounding)),
+                -2
+            );
+        }
+
+        // Below halfway rounds down with nearest
+        assert_eq!(
+            ticks(ts(14, 1, 10).with_new_timebase_rounded(secs, Rounding::Nearest)),
+            1
+        );
+        assert_eq!(
+            ticks(ts(-14, 1, 10).with_new_timebase_rounded(secs, Rounding::Nearest)),
+            -1
+        );
+    }
+
+    #[test]
+    fn rescale_overflow() {
+        let millis = TimeBase::MILLISECONDS;
+
+        assert!(ts(i64::MAX, 1, 1)
+            .checked_with_new_timebase_rounded(millis, Rounding::Down)
+            .is_none());
+        assert_eq!(
+            ticks(ts(i64::MAX, 1, 1).with_new_timebase(millis)),
+            i64::MAX
+        );
+        assert_eq!(
+            ticks(ts(i64::MIN + 1, 1, 1).with_new_timebase(millis)),
+            i64::MIN + 1
+        );
+    }
+
+    #[test]
+    fn add_and_sub() {
+        let sum = ts(1, 1, 2) + ts(1, 1, 3);
+        assert_eq!(sum.time_base(), TimeBase::new(1, 6));
+        assert_eq!(ticks(sum), 5);
+
+        let diff = ts(1, 1, 3) - ts(1, 1, 2);
+        assert_eq!(diff.time_base(), TimeBase::new(1, 6));
+        assert_eq!(ticks(diff), -1);
+
+        assert_eq!(ts(1, 1, 2).checked_add(ts(1, 1, 3)), Some(sum));
+        assert_eq!(ts(1, 1, 3).checked_sub(ts(1, 1, 2)), Some(diff));
+    }
+
+    #[test]
+    fn add_and_sub_overflow() {
+        let max = ts(i64::MAX, 1, 1);
+        let min = ts(i64::MIN + 1, 1, 1);
+        let one = ts(1, 1, 1);
+
+        assert!(max.checked_add(one).is_none());
+        assert!(min.checked_sub(one).is_none());
+        assert_eq!(ticks(max + one), i64::MAX);
+        assert_eq!(ticks(min - one), i64::MIN + 1);
+    }
+
+    #[test]
+    fn add_without_common_time_base() {
+        // The common TimeBase would be too fine to use with libav
+        let lhs = ts(1, 1, 2_147_483_647);
+        let rhs = ts(1, 1, 2_147_483_646);
+
+        assert!(lhs.checked_add(rhs).is_none());
+        assert!(lhs.checked_sub(rhs).is_none());
+
+        let sum = lhs + rhs;
+        assert_eq!(sum.time_base(), lhs.time_base());
+        assert_eq!(ticks(sum), 2);
+    }
+
+    #[test]
+    fn cross_base_comparisons() {
+        assert_eq!(ts(1, 1, 1), ts(1000, 1, 1000));
+        assert_eq!(ts(-3, 1, 2), ts(-1500, 1, 1000));
+        assert_eq!(hash(ts(1, 1, 1)), hash(ts(1000, 1, 1000)));
+
+        assert!(ts(1, 1, 3) < ts(334, 1, 1000));
+        assert!(ts(1, 1, 3) > ts(333, 1, 1000));
+        assert!(ts(-1, 1, 3) < ts(-333, 1, 1000));
+        assert_ne!(ts(1, 1, 3), ts(333, 1, 1000));
+    }
+}
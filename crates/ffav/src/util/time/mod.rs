@@ -22,7 +22,7 @@ pub use audio::*;
 pub use generic::*;
 pub use video::*;
 
-use crate::config::StreamConfig;
+use crate::{config::StreamConfig, error::Result};
 use num_rational::Ratio;
 
 /// Internal rational type for storing TimeBase and Framerate
@@ -34,8 +34,11 @@ type Rational = Ratio<u64>;
 
 /// A type which can be converted into a timestamp using the context of a Stream
 pub trait IntoStreamTimestamp<AV> {
-    /// Convert to a timestamp
-    fn into(self, stream: &StreamConfig<AV>) -> Timestamp;
+    /// Convert to a timestamp in the TimeBase of the stream
+    ///
+    /// This can fail if the stream is missing information required for the
+    /// conversion, eg. the frame-rate of a video stream.
+    fn into(self, stream: &StreamConfig<AV>) -> Result<Timestamp>;
 }
 
 /// Anything which can be directly converted to a timestamp can be converted
@@ -44,9 +47,9 @@ impl<T, AV> IntoStreamTimestamp<AV> for T
 where
     T: Into<Timestamp>,
 {
-    fn into(self, stream: &StreamConfig<AV>) -> Timestamp {
+    fn into(self, stream: &StreamConfig<AV>) -> Result<Timestamp> {
         let raw = self.into();
 
-        raw.with_new_timebase(stream.time_base())
+        Ok(raw.with_new_timebase(stream.time_base()))
     }
 }
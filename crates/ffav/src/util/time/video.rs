@@ -1,22 +1,125 @@
-use crate::util::marker::Video;
+use crate::{
+    config::StreamConfig,
+    error::{Error, Result},
+    util::marker::Video,
+};
+use ffav_sys::AVRational;
+use std::fmt;
 
-use super::{Rational, TimeBase, TimeBaseTicks};
+use super::{Rational, Rounding, TimeBase, TimeBaseTicks, Timestamp};
 
 /// The frame-rate of a Video stream, may be fractional
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct FrameRate(Rational);
 
+impl FrameRate {
+    /// Create a frame-rate of `numer / denom` frames per second
+    ///
+    /// Panics: If `numer` or `denom` is zero
+    pub fn new(numer: u64, denom: u64) -> Self {
+        assert!(numer != 0, "FrameRate numerator is zero");
+
+        FrameRate(Rational::new(numer, denom))
+    }
+
+    /// Create a frame-rate from an `AVRational`
+    ///
+    /// libav uses `0/0` or `0/1` for an unknown frame-rate, these result in None
+    pub(crate) fn from_av_rational(rational: &AVRational) -> Option<Self> {
+        if rational.num <= 0 || rational.den <= 0 {
+            None
+        } else {
+            Some(FrameRate::new(rational.num as u64, rational.den as u64))
+        }
+    }
+
+    /// Convert this frame-rate to an `AVRational` for use with libav functions
+    pub(crate) fn as_av_rational(self) -> AVRational {
+        AVRational {
+            num: *self.0.numer() as i32,
+            den: *self.0.denom() as i32,
+        }
+    }
+
+    /// Get the frame-rate as an approximate number of frames per second
+    pub fn as_f64(self) -> f64 {
+        *self.0.numer() as f64 / *self.0.denom() as f64
+    }
+
+    /// The TimeBase in which each tick is the duration of one frame
+    pub fn frame_time_base(self) -> TimeBase {
+        TimeBase::new(*self.0.denom(), *self.0.numer())
+    }
+}
+
+impl fmt::Display for FrameRate {
+    /// Formats the frame-rate as `numer/denom`, as accepted by libav options
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.0.numer(), self.0.denom())
+    }
+}
+
 /// The index of a single frame in a Video stream
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Frame(u64);
 
+impl Frame {
+    /// Create a new frame index, the first frame of a stream has index 0
+    pub fn new(index: u64) -> Self {
+        Frame(index)
+    }
+
+    /// Get the index of this frame
+    pub fn index(self) -> u64 {
+        self.0
+    }
+
+    /// Find the frame of a stream nearest to a timestamp
+    ///
+    /// This is the inverse of `IntoStreamTimestamp`, the frame index is
+    /// counted from the start time of the stream when it is known. Timestamps
+    /// before the start of the stream result in the first frame.
+    pub fn from_stream_timestamp(ts: Timestamp, stream: &StreamConfig<Video>) -> Result<Frame> {
+        let base = stream_frame_rate(stream)?.frame_time_base();
+
+        let ts = match stream.start_time() {
+            Some(start) => ts - start,
+            None => ts,
+        };
+
+        let index = ts
+            .with_new_timebase_rounded(base, Rounding::Nearest)
+            .get_ticks();
+
+        Ok(Frame::new(index.as_av_timestamp().max(0) as u64))
+    }
+}
+
+/// The frame-rate used to convert between frame indexes and timestamps
+fn stream_frame_rate(stream: &StreamConfig<Video>) -> Result<FrameRate> {
+    stream
+        .avg_frame_rate()
+        .or_else(|| stream.r_frame_rate())
+        .ok_or(Error::UnknownFrameRate)
+}
+
+/// The frame index is counted from the start time of the stream, when known,
+/// and rounded to the nearest tick of the stream's TimeBase. The inverse is
+/// `Frame::from_stream_timestamp()`.
 impl super::IntoStreamTimestamp<Video> for Frame {
-    fn into(self, stream: &crate::config::StreamConfig<Video>) -> super::Timestamp {
-        let frame_rate = stream.avg_frame_rate();
+    fn into(self, stream: &StreamConfig<Video>) -> Result<Timestamp> {
+        let frame_rate = stream_frame_rate(stream)?;
+
+        let ts = TimeBaseTicks::new(self.0 as i64)
+            .to_timestamp(frame_rate.frame_time_base())
+            .with_new_timebase_rounded(stream.time_base(), Rounding::Nearest);
 
-        TimeBaseTicks::new(self.0)
-            .to_timestamp(TimeBase::new(*frame_rate.0.numer(), *frame_rate.0.denom()))
+        // The start time is already in the stream's TimeBase so the sum is too
+        Ok(match stream.start_time() {
+            Some(start) => ts + start,
+            None => ts,
+        })
     }
 }
@@ -54,7 +54,7 @@ fn main() {
                 }
 
                 let mut frame = codec.get_next_frame().unwrap();
-                let plane = frame.plane(0);
+                let plane = frame.plane(0).unwrap();
 
                 for pix in plane.chunks_exact(plane.linesize) {
                     println!("Input: {:?}", pix);
@@ -65,7 +65,7 @@ fn main() {
             Err(Error::EoF) => break,
             Err(e) => panic!("{}", e),
             Ok(frame) => {
-                let plane = frame.plane(0);
+                let plane = frame.plane(0).unwrap();
                 for pix in plane
                     .chunks_exact(plane.linesize)
                     .flat_map(|line| line.chunks_exact(2))